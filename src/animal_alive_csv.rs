@@ -1,20 +1,118 @@
-use csv::Position;
+use csv::StringRecord;
 use std::error::Error;
 use std::fs::File;
-use std::io;
+use std::sync::Arc;
 
-use crate::animal_csv_shared_functions::{delete_all_animals_for_path, delete_one_animal_for_path};
+use crate::animal_csv_shared_functions::{
+    delete_all_animals_for_path, field, header_index, optional_from_csv, optional_to_csv,
+};
 use crate::animal_structs::*;
 use crate::custom_writers_and_readers::*;
+use crate::genetics::Genotype;
+use crate::session_cache::{AnimalsIndex, ANIMALS_ALIVE};
+use crate::transaction::Transaction;
 use crate::ANIMALS_ALIVE_FILE_PATH;
 
+pub const ANIMALS_ALIVE_HEADERS: [&str; 11] = [
+    "name",
+    "sex",
+    "id",
+    "energy",
+    "energy_at",
+    "born_at",
+    "bred_at",
+    "genotype",
+    "stage",
+    "habitat",
+    "health",
+];
+
+/// Genotypes are written like `color:green/albino;size:small/small`.
+impl Genotype {
+    pub fn to_csv(&self) -> String {
+        self.alleles
+            .iter()
+            .map(|(name, (first, second))| format!("{}:{}/{}", name, first, second))
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+
+    pub fn from_csv(field: &str) -> Result<Genotype, Box<dyn Error>> {
+        let mut genotype = Genotype::default();
+        for gene in field.split(';').filter(|gene| !gene.is_empty()) {
+            let (name, alleles) = gene.split_once(':').ok_or("Invalid genotype")?;
+            let (first, second) = alleles.split_once('/').ok_or("Invalid genotype")?;
+            genotype
+                .alleles
+                .insert(name.to_string(), (first.to_string(), second.to_string()));
+        }
+        Ok(genotype)
+    }
+}
+
 impl AnimalAlive {
-    pub fn to_csv(&self) -> [String; 2] {
-        [self.name(), self.sex_str()]
+    pub fn to_csv(&self) -> [String; 11] {
+        [
+            self.name(),
+            self.sex_str(),
+            self.id().to_string(),
+            self.energy().to_string(),
+            self.energy_at().to_string(),
+            self.born_at().to_string(),
+            optional_to_csv(self.bred_at()),
+            self.genotype().to_csv(),
+            self.stage().as_str().to_string(),
+            self.habitat.clone(),
+            self.health.as_str().to_string(),
+        ]
+    }
+
+    pub fn from_csv(record: &StringRecord) -> Result<AnimalAlive, Box<dyn Error>> {
+        Ok(AnimalAlive {
+            id: field(record, 2)?.parse()?,
+            name: field(record, 0)?.to_string(),
+            sex: sex_from_csv(field(record, 1)?)?,
+            energy: field(record, 3)?.parse()?,
+            energy_at: field(record, 4)?.parse()?,
+            born_at: field(record, 5)?.parse()?,
+            bred_at: optional_from_csv(field(record, 6)?)?,
+            genotype: Genotype::from_csv(field(record, 7)?)?,
+            stage: stage_from_csv(field(record, 8)?)?,
+            habitat: field(record, 9)?.to_string(),
+            health: health_from_csv(field(record, 10)?)?,
+        })
+    }
+}
+
+pub fn sex_from_csv(field: &str) -> Result<Sex, Box<dyn Error>> {
+    match field {
+        "male" => Ok(Sex::Male),
+        "female" => Ok(Sex::Female),
+        "hermaphrodite" => Ok(Sex::Hermaphrodite),
+        _ => Err("Invalid sex".into()),
+    }
+}
+
+pub fn stage_from_csv(field: &str) -> Result<LifeStage, Box<dyn Error>> {
+    match field {
+        "egg" => Ok(LifeStage::Egg),
+        "larva" => Ok(LifeStage::Larva),
+        "juvenile" => Ok(LifeStage::Juvenile),
+        "adult" => Ok(LifeStage::Adult),
+        _ => Err("Invalid life stage".into()),
     }
 }
 
-impl<W: io::Write> CustomWriter<W> {
+pub fn health_from_csv(field: &str) -> Result<Health, Box<dyn Error>> {
+    match field {
+        "susceptible" => Ok(Health::Susceptible),
+        "infected" => Ok(Health::Infected),
+        "recovered" => Ok(Health::Recovered),
+        _ => Err("Invalid health".into()),
+    }
+}
+
+impl<W: Durable> CustomWriter<W> {
     pub fn write_animal_alive(&mut self, animal: AnimalAlive) -> csv::Result<()> {
         self.inner.write_record(animal.to_csv())?;
         self.flush()?;
@@ -27,6 +125,8 @@ pub trait AnimalAliveReader {
         &mut self,
         animal_name: &str,
     ) -> Result<Option<AnimalAlive>, Box<dyn Error>>;
+    fn read_animal_alive_by_id(&mut self, id: u32) -> Result<Option<AnimalAlive>, Box<dyn Error>>;
+    fn read_animals_alive(&mut self) -> Result<Vec<AnimalAlive>, Box<dyn Error>>;
     fn has_both_sexes(&mut self, animal_name: &str) -> Result<bool, Box<dyn Error>>;
     fn count_animal(&mut self, animal_name: &str) -> Result<i32, Box<dyn Error>>;
 }
@@ -39,12 +139,7 @@ impl<R: std::io::Read + std::io::Seek> AnimalAliveReader for CustomReader<R> {
         for result in self.inner.records() {
             let record = result?;
             if record[0].to_owned() == animal_name {
-                let sex = match &record[1] {
-                    "male" => Sex::Male,
-                    "female" => Sex::Female,
-                    _ => return Err("Invalid sex".into()),
-                };
-                let animal = AnimalAlive::born(animal_name, sex);
+                let animal = AnimalAlive::from_csv(&record)?;
                 self.seek_to_beginning()?;
                 return Ok(Some(animal));
             }
@@ -52,20 +147,41 @@ impl<R: std::io::Read + std::io::Seek> AnimalAliveReader for CustomReader<R> {
         self.seek_to_beginning()?;
         Ok(None)
     }
+    fn read_animal_alive_by_id(&mut self, id: u32) -> Result<Option<AnimalAlive>, Box<dyn Error>> {
+        let animal = self
+            .read_animals_alive()?
+            .into_iter()
+            .find(|animal| animal.id() == id);
+        Ok(animal)
+    }
+    fn read_animals_alive(&mut self) -> Result<Vec<AnimalAlive>, Box<dyn Error>> {
+        let mut animals = Vec::new();
+        for result in self.inner.records() {
+            animals.push(AnimalAlive::from_csv(&result?)?);
+        }
+        self.seek_to_beginning()?;
+        Ok(animals)
+    }
+    /// Whether there are two adults able to act as mother and father, a
+    /// hermaphrodite being able to act as either.
     fn has_both_sexes(&mut self, animal_name: &str) -> Result<bool, Box<dyn Error>> {
-        let mut male_exists = false;
-        let mut female_exists = false;
+        let (mut males, mut females, mut hermaphrodites) = (0, 0, 0);
         for result in self.inner.records() {
             let record = result?;
-            if record[0].to_owned() == animal_name {
-                match &record[1] {
-                    "male" => male_exists = true,
-                    "female" => female_exists = true,
-                    _ => return Err("Invalid sex".into()),
+            if record[0].to_owned() == animal_name
+                && stage_from_csv(field(&record, 8)?)? == LifeStage::Adult
+            {
+                match sex_from_csv(field(&record, 1)?)? {
+                    Sex::Male => males += 1,
+                    Sex::Female => females += 1,
+                    Sex::Hermaphrodite => hermaphrodites += 1,
                 }
             }
-            if male_exists && female_exists {
-                self.inner.seek(Position::new())?;
+            if males + hermaphrodites > 0
+                && females + hermaphrodites > 0
+                && males + females + hermaphrodites >= 2
+            {
+                self.seek_to_beginning()?;
                 return Ok(true);
             }
         }
@@ -77,20 +193,103 @@ impl<R: std::io::Read + std::io::Seek> AnimalAliveReader for CustomReader<R> {
     }
 }
 
-pub fn kill_all_animals_alive(animal_name: &str) -> Result<(), Box<dyn Error>> {
-    delete_all_animals_for_path(animal_name, ANIMALS_ALIVE_FILE_PATH)
+pub fn kill_all_animals_alive(
+    transaction: &mut Transaction,
+    animal_name: &str,
+) -> Result<(), Box<dyn Error>> {
+    delete_all_animals_for_path(transaction, animal_name, ANIMALS_ALIVE_FILE_PATH)?;
+    let animal_name = animal_name.to_string();
+    ANIMALS_ALIVE.write_through_on_commit(transaction, move |animals| {
+        animals.retain(|animal| animal.name != animal_name)
+    })
 }
 
-pub fn kill_one_animal_alive(animal_name: &str) -> Result<(), Box<dyn Error>> {
-    delete_one_animal_for_path(animal_name, ANIMALS_ALIVE_FILE_PATH)
+/// Removes the animals with these ids, returning them as they were.
+pub fn kill_animals_alive_by_id(
+    transaction: &mut Transaction,
+    ids: &[u32],
+) -> Result<Vec<AnimalAlive>, Box<dyn Error>> {
+    let mut killed = Vec::new();
+    transaction.rewrite(ANIMALS_ALIVE_FILE_PATH, |headers, animal| {
+        let id: u32 = animal[header_index(headers, "id")?].parse()?;
+        if ids.contains(&id) {
+            killed.push(AnimalAlive::from_csv(&animal)?);
+            Ok(None)
+        } else {
+            Ok(Some(animal))
+        }
+    })?;
+    let ids = ids.to_vec();
+    ANIMALS_ALIVE.write_through_on_commit(transaction, move |animals| {
+        animals.retain(|animal| !ids.contains(&animal.id))
+    })?;
+    Ok(killed)
+}
+
+/// Brings back an animal removed from the living ones.
+pub fn revive_animal_alive(
+    transaction: &mut Transaction,
+    animal: &AnimalAlive,
+) -> Result<(), Box<dyn Error>> {
+    transaction.append(ANIMALS_ALIVE_FILE_PATH, &animal.to_csv())?;
+    let animal = animal.clone();
+    ANIMALS_ALIVE.write_through_on_commit(transaction, move |animals| animals.push(animal))
+}
+
+/// Stages newborns at the end of the animals alive.
+pub fn append_animals_alive(
+    transaction: &mut Transaction,
+    newborns: &[AnimalAlive],
+) -> Result<(), Box<dyn Error>> {
+    let rows = newborns
+        .iter()
+        .map(|animal| animal.to_csv().to_vec())
+        .collect::<Vec<_>>();
+    transaction.append_all(ANIMALS_ALIVE_FILE_PATH, &rows)?;
+    let newborns = newborns.to_vec();
+    ANIMALS_ALIVE.write_through_on_commit(transaction, move |animals| animals.extend(newborns))
+}
+
+/// Saves an animal over the row with its id, returning the row it replaced.
+pub fn update_animal_alive(
+    transaction: &mut Transaction,
+    animal: &AnimalAlive,
+) -> Result<Option<AnimalAlive>, Box<dyn Error>> {
+    let previous = update_animal_alive_for_path(transaction, animal, ANIMALS_ALIVE_FILE_PATH)?;
+    let animal = animal.clone();
+    ANIMALS_ALIVE.write_through_on_commit(transaction, move |animals| {
+        for saved in animals.iter_mut().filter(|saved| saved.id == animal.id) {
+            *saved = animal.clone();
+        }
+    })?;
+    Ok(previous)
+}
+
+fn update_animal_alive_for_path(
+    transaction: &mut Transaction,
+    animal: &AnimalAlive,
+    path: &str,
+) -> Result<Option<AnimalAlive>, Box<dyn Error>> {
+    let mut previous = None;
+    transaction.rewrite(path, |headers, record| {
+        let id: u32 = record[header_index(headers, "id")?].parse()?;
+        if id == animal.id() {
+            previous = Some(AnimalAlive::from_csv(&record)?);
+            Ok(Some(StringRecord::from(animal.to_csv().to_vec())))
+        } else {
+            Ok(Some(record))
+        }
+    })?;
+    Ok(previous)
 }
 
 pub fn writer_animals_alive() -> Result<CustomWriter<File>, Box<dyn Error>> {
     create_writer_append_for_path(ANIMALS_ALIVE_FILE_PATH)
 }
 
-pub fn reader_animals_alive() -> Result<CustomReader<File>, Box<dyn Error>> {
-    create_reader_for_path(ANIMALS_ALIVE_FILE_PATH)
+/// Animals alive as last loaded this session.
+pub fn reader_animals_alive() -> Result<Arc<AnimalsIndex>, Box<dyn Error>> {
+    ANIMALS_ALIVE.get()
 }
 
 #[cfg(test)]
@@ -100,6 +299,7 @@ mod tests {
     use crate::animal_structs::animals_alive_models::*;
     use crate::csv_files_creator::create_test_animals_alive;
     use crate::custom_writers_and_readers::{reader_for_test, writer_for_test};
+    use crate::{test_dir, test_path};
 
     #[test]
     fn test_read_animal_alive() -> Result<(), Box<dyn Error>> {
@@ -135,6 +335,44 @@ mod tests {
         assert!(reader.has_both_sexes("snake")?);
         assert!(!reader.has_both_sexes("chameleon")?);
 
+        let mut snail_egg = AnimalAlive::born(4, "snail", Sex::Hermaphrodite);
+        snail_egg.stage = LifeStage::Egg;
+        writer.write_animal_alive(snail_egg)?;
+        writer.write_animal_alive(AnimalAlive::born(5, "snail", Sex::Hermaphrodite))?;
+        assert!(!reader.has_both_sexes("snail")?);
+        writer.write_animal_alive(AnimalAlive::born(6, "snail", Sex::Hermaphrodite))?;
+        assert!(reader.has_both_sexes("snail")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_animal_alive() -> Result<(), Box<dyn Error>> {
+        create_test_animals_alive()?;
+
+        let mut writer = writer_for_test()?;
+
+        writer.write_animal_alive(snake_female())?;
+        writer.write_animal_alive(snake_male())?;
+
+        let mut hungry_snake = snake_male();
+        hungry_snake.energy = 5;
+        hungry_snake.genotype = Genotype::from_csv("color:green/albino")?;
+        hungry_snake.health = Health::Infected;
+        let mut transaction = Transaction::in_dir(&test_dir());
+        assert_eq!(
+            update_animal_alive_for_path(&mut transaction, &hungry_snake, &test_path())?,
+            Some(snake_male())
+        );
+        transaction.commit()?;
+
+        let mut reader = reader_for_test()?;
+        let animals = reader.read_animals_alive()?;
+
+        assert_eq!(animals[0].energy(), MAX_ENERGY);
+        assert_eq!(animals[1].energy(), 5);
+        assert_eq!(reader.read_animal_alive_by_id(2)?, Some(hungry_snake));
+        assert!(reader.read_animal_alive_by_id(3)?.is_none());
         Ok(())
     }
 }
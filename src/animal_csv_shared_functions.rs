@@ -1,57 +1,78 @@
+use csv::StringRecord;
 use std::error::Error;
 
-use crate::custom_writers_and_readers::{create_reader_for_path, create_writer_append_for_path};
-use crate::temp_file_handler::*;
+use crate::transaction::Transaction;
+
+/// Rewrites `path` through a temp file, keeping the records for which
+/// `rewrite` returns `Some`. The headers are handed to `rewrite` so it can
+/// locate its columns.
+pub fn rewrite_records_for_path<F>(path: &str, rewrite: F) -> Result<(), Box<dyn Error>>
+where
+    F: FnMut(&StringRecord, StringRecord) -> Result<Option<StringRecord>, Box<dyn Error>>,
+{
+    let mut transaction = Transaction::new();
+    transaction.rewrite(path, rewrite)?;
+    transaction.commit()
+}
+
+pub fn header_index(headers: &StringRecord, header: &str) -> Result<usize, Box<dyn Error>> {
+    headers
+        .iter()
+        .position(|h| h == header)
+        .ok_or_else(|| format!("Missing {} column", header).into())
+}
+
+/// Field at `index`, or an error for a row too short to have it.
+pub fn field(record: &StringRecord, index: usize) -> Result<&str, Box<dyn Error>> {
+    record
+        .get(index)
+        .ok_or_else(|| format!("Missing field {} in csv", index + 1).into())
+}
+
+pub fn optional_to_csv<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+pub fn optional_from_csv<T: std::str::FromStr>(field: &str) -> Result<Option<T>, T::Err> {
+    if field.is_empty() {
+        Ok(None)
+    } else {
+        field.parse().map(Some)
+    }
+}
 
 fn delete_animal_for_path(
+    transaction: &mut Transaction,
     path: &str,
     animal_name: &str,
     delete_all: bool,
 ) -> Result<(), Box<dyn Error>> {
-    let temp_file = create_temp_file(path)?;
-    let temp_file_path = temp_file.path().to_str().unwrap();
-    let mut temp_file_writer = create_writer_append_for_path(temp_file_path)?;
-
-    let mut path_reader = create_reader_for_path(path)?;
-
-    temp_file_writer
-        .inner
-        .write_record(path_reader.inner.headers()?)?;
-
-    let header_name_index = path_reader
-        .inner
-        .headers()?
-        .iter()
-        .position(|h| h == "name")
-        .unwrap();
-
     let mut animal_killed = false;
-    for result in path_reader.inner.records() {
-        let animal_alive = result?;
-        if animal_alive.get(header_name_index) != Some(animal_name)
-            || (animal_killed && !delete_all)
-        {
-            temp_file_writer.inner.write_record(&animal_alive)?;
+    transaction.rewrite(path, |headers, animal| {
+        let header_name_index = header_index(headers, "name")?;
+        if animal.get(header_name_index) != Some(animal_name) || (animal_killed && !delete_all) {
+            Ok(Some(animal))
         } else {
-            if !animal_killed {
-                animal_killed = true;
-            }
+            animal_killed = true;
+            Ok(None)
         }
-    }
-
-    temp_file_writer.flush()?;
-
-    std::fs::rename(&temp_file_path, path)?;
-
-    Ok(())
+    })
 }
 
-pub fn delete_all_animals_for_path(animal_name: &str, path: &str) -> Result<(), Box<dyn Error>> {
-    delete_animal_for_path(path, animal_name, true)
+pub fn delete_all_animals_for_path(
+    transaction: &mut Transaction,
+    animal_name: &str,
+    path: &str,
+) -> Result<(), Box<dyn Error>> {
+    delete_animal_for_path(transaction, path, animal_name, true)
 }
 
-pub fn delete_one_animal_for_path(animal_name: &str, path: &str) -> Result<(), Box<dyn Error>> {
-    delete_animal_for_path(path, animal_name, false)
+pub fn delete_one_animal_for_path(
+    transaction: &mut Transaction,
+    animal_name: &str,
+    path: &str,
+) -> Result<(), Box<dyn Error>> {
+    delete_animal_for_path(transaction, path, animal_name, false)
 }
 
 #[cfg(test)]
@@ -61,16 +82,18 @@ mod tests {
     use crate::animal_structs::animals_data_models::*;
     use crate::csv_files_creator::{create_test_animals_alive, create_test_animals_data};
     use crate::custom_writers_and_readers::{reader_for_test, writer_for_test};
-    use crate::TEST_PATH;
+    use crate::{test_dir, test_path};
 
     fn delete_all_animals_for_test(animal_name: &str) -> Result<(), Box<dyn Error>> {
-        delete_all_animals_for_path(animal_name, TEST_PATH)?;
-        Ok(())
+        let mut transaction = Transaction::in_dir(&test_dir());
+        delete_all_animals_for_path(&mut transaction, animal_name, &test_path())?;
+        transaction.commit()
     }
 
     fn delete_one_animal_for_test(animal_name: &str) -> Result<(), Box<dyn Error>> {
-        delete_one_animal_for_path(animal_name, TEST_PATH)?;
-        Ok(())
+        let mut transaction = Transaction::in_dir(&test_dir());
+        delete_one_animal_for_path(&mut transaction, animal_name, &test_path())?;
+        transaction.commit()
     }
 
     #[test]
@@ -1,25 +1,191 @@
-use csv::Position;
+use csv::StringRecord;
 use std::error::Error;
 use std::fs::File;
-use std::io;
+use std::sync::Arc;
 
-use crate::animal_csv_shared_functions::delete_one_animal_for_path;
+use crate::animal_alive_csv::sex_from_csv;
+use crate::animal_csv_shared_functions::{
+    delete_one_animal_for_path, field, optional_from_csv, optional_to_csv,
+};
 use crate::animal_structs::*;
+use crate::climate::Season;
+use crate::custom_string_methods::CustomStringMethods;
 use crate::custom_writers_and_readers::*;
+use crate::genetics::{Dominance, HeritableTrait};
+use crate::session_cache::{SpeciesIndex, SPECIES};
+use crate::transaction::Transaction;
 use crate::ANIMALS_DATA_FILE_PATH;
 
+pub const ANIMALS_DATA_HEADERS: [&str; 15] = [
+    "name",
+    "class",
+    "predators",
+    "preys",
+    "lifespan_days",
+    "maturity_days",
+    "breeding_cooldown_days",
+    "traits",
+    "reproduction",
+    "pivotal_temperature",
+    "incubation_days",
+    "juvenile_days",
+    "clutch_size",
+    "breeding_seasons",
+    "carrying_capacity",
+];
+
+/// Pivotal temperatures are written like `29.5:2:female`, with the celsius,
+/// transitional range and the sex hatching from warm eggs.
+pub fn pivotal_temperature_to_csv(pivotal_temperature: &Option<PivotalTemperature>) -> String {
+    match pivotal_temperature {
+        Some(pivotal_temperature) => format!(
+            "{}:{}:{}",
+            pivotal_temperature.celsius,
+            pivotal_temperature.transitional_range,
+            pivotal_temperature.warm_sex.as_str()
+        ),
+        None => String::new(),
+    }
+}
+
+pub fn pivotal_temperature_from_csv(
+    field: &str,
+) -> Result<Option<PivotalTemperature>, Box<dyn Error>> {
+    if field.is_empty() {
+        return Ok(None);
+    }
+    let parts = field.split(':').collect::<Vec<_>>();
+    let [celsius, transitional_range, warm_sex] = parts[..] else {
+        return Err("Invalid pivotal temperature in csv".into());
+    };
+    Ok(Some(PivotalTemperature {
+        celsius: celsius.parse()?,
+        transitional_range: transitional_range.parse()?,
+        warm_sex: sex_from_csv(warm_sex)?,
+    }))
+}
+
+/// Seasons are written like `spring-summer`.
+pub fn seasons_to_csv(seasons: &[Season]) -> String {
+    seasons
+        .iter()
+        .map(|season| season.as_str())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+pub fn seasons_from_csv(field: &str) -> Result<Vec<Season>, Box<dyn Error>> {
+    field
+        .split('-')
+        .map(|season| match season {
+            "spring" => Ok(Season::Spring),
+            "summer" => Ok(Season::Summer),
+            "autumn" => Ok(Season::Autumn),
+            "winter" => Ok(Season::Winter),
+            _ => Err("Invalid season".into()),
+        })
+        .collect()
+}
+
+/// Traits are written like `color:green>albino;size:large=small`, where `>`
+/// reads "dominant over" and `=` "codominant with".
+pub fn traits_to_csv(traits: &[HeritableTrait]) -> String {
+    traits
+        .iter()
+        .map(|heritable_trait| {
+            let dominance = match heritable_trait.dominance {
+                Dominance::Complete => '>',
+                Dominance::Codominant => '=',
+            };
+            format!(
+                "{}:{}{}{}",
+                heritable_trait.name,
+                heritable_trait.dominant,
+                dominance,
+                heritable_trait.recessive
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+pub fn traits_from_csv(field: &str) -> Result<Vec<HeritableTrait>, Box<dyn Error>> {
+    field
+        .split(';')
+        .filter(|heritable_trait| !heritable_trait.is_empty())
+        .map(|heritable_trait| {
+            let (name, alleles) = heritable_trait
+                .split_once(':')
+                .ok_or("Invalid trait in csv")?;
+            let (dominant, recessive, dominance) = match alleles.split_once('>') {
+                Some((dominant, recessive)) => (dominant, recessive, Dominance::Complete),
+                None => {
+                    let (dominant, recessive) =
+                        alleles.split_once('=').ok_or("Invalid trait in csv")?;
+                    (dominant, recessive, Dominance::Codominant)
+                }
+            };
+            Ok(HeritableTrait::new(name, dominant, recessive, dominance))
+        })
+        .collect()
+}
+
 impl AnimalData {
-    pub fn to_csv(&self) -> [String; 4] {
+    pub fn to_csv(&self) -> [String; 15] {
         [
             self.name(),
             self.class_str(),
             self.predators_str(),
             self.preys_str(),
+            self.lifespan_days.to_string(),
+            self.maturity_days.to_string(),
+            self.breeding_cooldown_days.to_string(),
+            traits_to_csv(&self.traits),
+            self.reproduction.as_str().to_string(),
+            pivotal_temperature_to_csv(&self.pivotal_temperature),
+            self.incubation_days.to_string(),
+            self.juvenile_days.to_string(),
+            self.clutch_size.to_string(),
+            seasons_to_csv(&self.breeding_seasons),
+            optional_to_csv(self.carrying_capacity),
         ]
     }
+
+    pub fn from_csv(record: &StringRecord) -> Result<AnimalData, Box<dyn Error>> {
+        let class = match field(record, 1)? {
+            "mammal" => Class::Mammal,
+            "bird" => Class::Bird,
+            "amphibian" => Class::Amphibian,
+            "arthropod" => Class::Arthropod,
+            "fish" => Class::Fish,
+            "reptile" => Class::Reptile,
+            _ => return Err("Invalid class in csv".into()),
+        };
+        let predators = field(record, 2)?
+            .split('-')
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let preys = field(record, 3)?
+            .split('-')
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let mut animal = AnimalData::new(field(record, 0)?.to_string(), class, predators, preys);
+        animal.lifespan_days = field(record, 4)?.parse()?;
+        animal.maturity_days = field(record, 5)?.parse()?;
+        animal.breeding_cooldown_days = field(record, 6)?.parse()?;
+        animal.traits = traits_from_csv(field(record, 7)?)?;
+        animal.reproduction = field(record, 8)?.to_string().to_reproduction_mode()?;
+        animal.pivotal_temperature = pivotal_temperature_from_csv(field(record, 9)?)?;
+        animal.incubation_days = field(record, 10)?.parse()?;
+        animal.juvenile_days = field(record, 11)?.parse()?;
+        animal.clutch_size = field(record, 12)?.parse()?;
+        animal.breeding_seasons = seasons_from_csv(field(record, 13)?)?;
+        animal.carrying_capacity = optional_from_csv(field(record, 14)?)?;
+        Ok(animal)
+    }
 }
 
-impl<W: io::Write> CustomWriter<W> {
+impl<W: Durable> CustomWriter<W> {
     pub fn write_animal_data(&mut self, animal: &AnimalData) -> csv::Result<()> {
         self.inner.write_record(animal.to_csv())?;
         self.flush()?;
@@ -30,6 +196,7 @@ impl<W: io::Write> CustomWriter<W> {
 pub trait AnimalDataReader {
     fn read_animal_data(&mut self, animal_name: &str)
         -> Result<Option<AnimalData>, Box<dyn Error>>;
+    fn read_animals_data(&mut self) -> Result<Vec<AnimalData>, Box<dyn Error>>;
 }
 
 impl<R: std::io::Read + std::io::Seek> AnimalDataReader for CustomReader<R> {
@@ -40,37 +207,54 @@ impl<R: std::io::Read + std::io::Seek> AnimalDataReader for CustomReader<R> {
         for result in self.inner.records() {
             let record = result?;
             if record[0].to_owned() == animal_name {
-                let class = match &record[1] {
-                    "mammal" => Class::Mammal,
-                    "bird" => Class::Bird,
-                    "amphibian" => Class::Amphibian,
-                    "arthropod" => Class::Arthropod,
-                    "fish" => Class::Fish,
-                    "reptile" => Class::Reptile,
-                    _ => return Err("Invalid class in csv".into()),
-                };
-                let predators = record[2].split('-').map(String::from).collect::<Vec<_>>();
-                let preys = record[3].split('-').map(String::from).collect::<Vec<_>>();
-                let animal = AnimalData::new(animal_name.to_string(), class, predators, preys);
-                self.inner.seek(Position::new())?;
+                let animal = AnimalData::from_csv(&record)?;
+                self.seek_to_beginning()?;
                 return Ok(Some(animal));
             }
         }
         self.seek_to_beginning()?;
         Ok(None)
     }
+    fn read_animals_data(&mut self) -> Result<Vec<AnimalData>, Box<dyn Error>> {
+        let mut animals = Vec::new();
+        for result in self.inner.records() {
+            animals.push(AnimalData::from_csv(&result?)?);
+        }
+        self.seek_to_beginning()?;
+        Ok(animals)
+    }
 }
 
-pub fn delete_animal_data(animal_name: &str) -> Result<(), Box<dyn Error>> {
-    delete_one_animal_for_path(animal_name, ANIMALS_DATA_FILE_PATH)
+pub fn delete_animal_data(
+    transaction: &mut Transaction,
+    animal_name: &str,
+) -> Result<(), Box<dyn Error>> {
+    delete_one_animal_for_path(transaction, animal_name, ANIMALS_DATA_FILE_PATH)?;
+    let animal_name = animal_name.to_string();
+    SPECIES.write_through_on_commit(transaction, move |species| {
+        if let Some(index) = species.iter().position(|animal| animal.name == animal_name) {
+            species.remove(index);
+        }
+    })
+}
+
+/// Adds a species row, or puts one back, at the end of the file.
+pub fn append_animal_data(
+    transaction: &mut Transaction,
+    animal_data: &AnimalData,
+) -> Result<(), Box<dyn Error>> {
+    transaction.append(ANIMALS_DATA_FILE_PATH, &animal_data.to_csv())?;
+    let animal_data = animal_data.clone();
+    SPECIES.write_through_on_commit(transaction, move |species| species.push(animal_data))
 }
 
 pub fn writer_animals_data() -> Result<CustomWriter<File>, Box<dyn Error>> {
     create_writer_append_for_path(ANIMALS_DATA_FILE_PATH)
 }
 
-pub fn reader_animals_data() -> Result<CustomReader<File>, Box<dyn Error>> {
-    create_reader_for_path(ANIMALS_DATA_FILE_PATH)
+/// Species as last loaded this session.
+pub fn reader_animals_data() -> Result<Arc<SpeciesIndex>, Box<dyn Error>> {
+    SPECIES.get()
 }
 
 #[cfg(test)]
@@ -97,4 +281,42 @@ mod tests {
         assert!(reader.read_animal_data("chameleon")?.is_some());
         Ok(())
     }
+
+    #[test]
+    fn test_traits_csv() -> Result<(), Box<dyn Error>> {
+        let traits = vec![
+            HeritableTrait::new("color", "green", "albino", Dominance::Complete),
+            HeritableTrait::new("size", "large", "small", Dominance::Codominant),
+        ];
+        let field = traits_to_csv(&traits);
+        assert_eq!(field, "color:green>albino;size:large=small");
+        assert_eq!(traits_from_csv(&field)?, traits);
+        assert!(traits_from_csv("")?.is_empty());
+        assert!(traits_from_csv("color:green").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pivotal_temperature_csv() -> Result<(), Box<dyn Error>> {
+        let pivotal_temperature = Some(PivotalTemperature {
+            celsius: 29.5,
+            transitional_range: 2.0,
+            warm_sex: Sex::Female,
+        });
+        let field = pivotal_temperature_to_csv(&pivotal_temperature);
+        assert_eq!(field, "29.5:2:female");
+        assert_eq!(pivotal_temperature_from_csv(&field)?, pivotal_temperature);
+        assert_eq!(pivotal_temperature_from_csv("")?, None);
+        assert!(pivotal_temperature_from_csv("29.5:female").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_seasons_csv() -> Result<(), Box<dyn Error>> {
+        let seasons = vec![Season::Spring, Season::Summer];
+        assert_eq!(seasons_to_csv(&seasons), "spring-summer");
+        assert_eq!(seasons_from_csv("spring-summer")?, seasons);
+        assert!(seasons_from_csv("monsoon").is_err());
+        Ok(())
+    }
 }
@@ -1,10 +1,59 @@
-#[derive(PartialEq, Debug)]
+use rand::prelude::{Rng, SliceRandom};
+
+use crate::climate::Season;
+use crate::clock;
+use crate::genetics::{Genotype, HeritableTrait};
+
+pub const MAX_ENERGY: i64 = 100;
+pub const HUNGRY_ENERGY: i64 = 30;
+
+// Gompertz mortality past the lifespan: initial hazard and how fast it grows
+// per lifespan of overage.
+const OLD_AGE_HAZARD: f64 = 5.0;
+const OLD_AGE_HAZARD_GROWTH: f64 = 10.0;
+
+// Cold-blooded animals are torpid below the first temperature and fully
+// active above the second one.
+const COLD_TORPOR_TEMPERATURE: f64 = 10.0;
+const COLD_ACTIVE_TEMPERATURE: f64 = 25.0;
+
+/// Share of the usual births still happening in a population of `population`
+/// animals living where at most `capacity` fit, falling logistically to 0
+/// once the capacity is reached.
+pub fn logistic_birth_chance(population: u32, capacity: u32) -> f64 {
+    if capacity == 0 {
+        return 0.0;
+    }
+    (1.0 - population as f64 / capacity as f64).clamp(0.0, 1.0)
+}
+
+#[derive(PartialEq, Debug, Clone)]
 pub enum BloodType {
     Warm,
     Cold,
 }
 
-#[derive(PartialEq, Debug)]
+impl BloodType {
+    pub fn energy_loss_per_day(&self) -> i64 {
+        match self {
+            BloodType::Warm => 20,
+            BloodType::Cold => 5,
+        }
+    }
+
+    /// Share of its usual activity an animal keeps at an ambient
+    /// temperature, from 0 when torpid to 1.
+    pub fn activity(&self, celsius: f64) -> f64 {
+        match self {
+            BloodType::Warm => 1.0,
+            BloodType::Cold => ((celsius - COLD_TORPOR_TEMPERATURE)
+                / (COLD_ACTIVE_TEMPERATURE - COLD_TORPOR_TEMPERATURE))
+                .clamp(0.0, 1.0),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
 pub enum Class {
     Reptile,
     Mammal,
@@ -14,24 +63,257 @@ pub enum Class {
     Amphibian,
 }
 
+impl Class {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Class::Mammal => "mammal",
+            Class::Bird => "bird",
+            Class::Amphibian => "amphibian",
+            Class::Arthropod => "arthropod",
+            Class::Fish => "fish",
+            Class::Reptile => "reptile",
+        }
+    }
+
+    pub fn default_lifespan_days(&self) -> u32 {
+        let years = match self {
+            Class::Mammal | Class::Reptile => 15,
+            Class::Bird => 10,
+            Class::Amphibian => 8,
+            Class::Fish => 5,
+            Class::Arthropod => 1,
+        };
+        years * 365
+    }
+
+    pub fn default_maturity_days(&self) -> u32 {
+        match self {
+            Class::Mammal | Class::Reptile => 3 * 365,
+            Class::Bird | Class::Amphibian => 2 * 365,
+            Class::Fish => 365,
+            Class::Arthropod => 30,
+        }
+    }
+
+    pub fn default_breeding_cooldown_days(&self) -> u32 {
+        match self {
+            Class::Arthropod => 7,
+            Class::Fish | Class::Amphibian => 90,
+            Class::Mammal | Class::Reptile | Class::Bird => 365,
+        }
+    }
+
+    pub fn lives_in_water(&self) -> bool {
+        self == &Class::Fish
+    }
+
+    pub fn lays_eggs(&self) -> bool {
+        self != &Class::Mammal
+    }
+
+    /// Whether the young are larvae that metamorphose into adults.
+    pub fn metamorphoses(&self) -> bool {
+        matches!(self, Class::Amphibian | Class::Arthropod)
+    }
+
+    pub fn default_incubation_days(&self) -> u32 {
+        match self {
+            Class::Mammal => 0,
+            Class::Arthropod => 7,
+            Class::Fish | Class::Amphibian => 10,
+            Class::Bird => 21,
+            Class::Reptile => 60,
+        }
+    }
+
+    pub fn default_juvenile_days(&self) -> u32 {
+        match self {
+            Class::Arthropod => 21,
+            Class::Fish | Class::Amphibian | Class::Bird => 60,
+            Class::Mammal | Class::Reptile => 180,
+        }
+    }
+
+    pub fn default_clutch_size(&self) -> u32 {
+        match self {
+            Class::Mammal => 1,
+            Class::Bird => 3,
+            Class::Reptile => 6,
+            Class::Amphibian | Class::Arthropod => 10,
+            Class::Fish => 20,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum LifeStage {
+    Egg,
+    /// Young of amphibians and arthropods, before metamorphosis.
+    Larva,
+    /// Young of the other classes.
+    Juvenile,
+    Adult,
+}
+
+impl LifeStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LifeStage::Egg => "egg",
+            LifeStage::Larva => "larva",
+            LifeStage::Juvenile => "juvenile",
+            LifeStage::Adult => "adult",
+        }
+    }
+}
+
 #[derive(PartialEq, Debug, Clone, Copy)]
 pub enum Sex {
     Male,
     Female,
+    /// Both sexes at once, like snails.
+    Hermaphrodite,
 }
 
-#[derive(PartialEq, Debug)]
+impl Sex {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Sex::Male => "male",
+            Sex::Female => "female",
+            Sex::Hermaphrodite => "hermaphrodite",
+        }
+    }
+}
+
+/// Temperature-dependent sex determination: eggs incubated above the pivotal
+/// temperature mostly hatch as `warm_sex`, the shift happening within the
+/// transitional range around it.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct PivotalTemperature {
+    pub celsius: f64,
+    pub transitional_range: f64,
+    pub warm_sex: Sex,
+}
+
+impl PivotalTemperature {
+    pub fn cold_sex(&self) -> Sex {
+        match self.warm_sex {
+            Sex::Male => Sex::Female,
+            _ => Sex::Male,
+        }
+    }
+
+    /// Share of eggs hatching as `warm_sex` at `temperature`, following a
+    /// logistic curve going from 5% to 95% across the transitional range.
+    pub fn warm_sex_ratio(&self, temperature: f64) -> f64 {
+        let slope = 2.0 * 19f64.ln() / self.transitional_range;
+        1.0 / (1.0 + (-slope * (temperature - self.celsius)).exp())
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ReproductionMode {
+    /// A female and a male are needed.
+    Sexual,
+    /// Females alone lay clones of themselves, like whiptail lizards.
+    Asexual,
+    /// Any two hermaphrodites can mate.
+    Hermaphrodite,
+    /// Born males turn female with age, like clownfish.
+    Protandrous,
+    /// Born females turn male with age, like wrasses.
+    Protogynous,
+}
+
+impl ReproductionMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReproductionMode::Sexual => "sexual",
+            ReproductionMode::Asexual => "asexual",
+            ReproductionMode::Hermaphrodite => "hermaphrodite",
+            ReproductionMode::Protandrous => "protandrous",
+            ReproductionMode::Protogynous => "protogynous",
+        }
+    }
+
+    /// Sex a spawned animal ends up with when the user asked for `sex`.
+    pub fn spawn_sex(&self, sex: Sex) -> Sex {
+        match self {
+            ReproductionMode::Asexual => Sex::Female,
+            ReproductionMode::Hermaphrodite => Sex::Hermaphrodite,
+            ReproductionMode::Sexual
+            | ReproductionMode::Protandrous
+            | ReproductionMode::Protogynous => sex,
+        }
+    }
+
+    /// Sexes offspring can be born with.
+    pub fn offspring_sexes(&self) -> &'static [Sex] {
+        match self {
+            ReproductionMode::Sexual => &[Sex::Male, Sex::Female],
+            ReproductionMode::Asexual => &[Sex::Female],
+            ReproductionMode::Hermaphrodite => &[Sex::Hermaphrodite],
+            ReproductionMode::Protandrous => &[Sex::Male],
+            ReproductionMode::Protogynous => &[Sex::Female],
+        }
+    }
+
+    /// Sexes of the mother and of the father, if one is needed.
+    pub fn parent_sexes(&self) -> (Sex, Option<Sex>) {
+        match self {
+            ReproductionMode::Asexual => (Sex::Female, None),
+            ReproductionMode::Hermaphrodite => (Sex::Hermaphrodite, Some(Sex::Hermaphrodite)),
+            ReproductionMode::Sexual
+            | ReproductionMode::Protandrous
+            | ReproductionMode::Protogynous => (Sex::Female, Some(Sex::Male)),
+        }
+    }
+
+    /// Sex an animal switches from and to once old enough.
+    pub fn sex_change(&self) -> Option<(Sex, Sex)> {
+        match self {
+            ReproductionMode::Protandrous => Some((Sex::Male, Sex::Female)),
+            ReproductionMode::Protogynous => Some((Sex::Female, Sex::Male)),
+            ReproductionMode::Sexual
+            | ReproductionMode::Asexual
+            | ReproductionMode::Hermaphrodite => None,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
 pub struct AnimalData {
     pub name: String,
     pub class: Class,
     pub predators: Vec<String>,
     pub preys: Vec<String>,
+    pub lifespan_days: u32,
+    pub maturity_days: u32,
+    pub breeding_cooldown_days: u32,
+    pub traits: Vec<HeritableTrait>,
+    pub reproduction: ReproductionMode,
+    pub pivotal_temperature: Option<PivotalTemperature>,
+    pub incubation_days: u32,
+    pub juvenile_days: u32,
+    pub clutch_size: u32,
+    pub breeding_seasons: Vec<Season>,
+    pub carrying_capacity: Option<u32>,
 }
 
 impl AnimalData {
     pub fn new(name: String, class: Class, predators: Vec<String>, preys: Vec<String>) -> Self {
         AnimalData {
             name,
+            lifespan_days: class.default_lifespan_days(),
+            maturity_days: class.default_maturity_days(),
+            breeding_cooldown_days: class.default_breeding_cooldown_days(),
+            traits: Vec::new(),
+            reproduction: ReproductionMode::Sexual,
+            pivotal_temperature: None,
+            incubation_days: class.default_incubation_days(),
+            juvenile_days: class.default_juvenile_days(),
+            clutch_size: class.default_clutch_size(),
+            breeding_seasons: Season::ALL.to_vec(),
+            carrying_capacity: None,
             class,
             predators,
             preys,
@@ -46,15 +328,7 @@ impl AnimalData {
     }
 
     pub fn class_str(&self) -> String {
-        let class = match &self.class() {
-            Class::Mammal => "mammal",
-            Class::Bird => "bird",
-            Class::Amphibian => "amphibian",
-            Class::Arthropod => "arthropod",
-            Class::Fish => "fish",
-            Class::Reptile => "reptile",
-        };
-        class.to_string()
+        self.class().as_str().to_string()
     }
 
     pub fn predators(&self) -> &Vec<String> {
@@ -73,12 +347,104 @@ impl AnimalData {
         self.preys.join("-")
     }
 
+    /// Whether this species would eat the other one, as told by either side.
+    pub fn hunts(&self, other: &AnimalData) -> bool {
+        self.name != other.name
+            && (self.preys.contains(&other.name) || other.predators.contains(&self.name))
+    }
+
     pub fn blood_type(&self) -> BloodType {
         match &self.class() {
             Class::Mammal | Class::Bird => BloodType::Warm,
             Class::Amphibian | Class::Arthropod | Class::Fish | Class::Reptile => BloodType::Cold,
         }
     }
+
+    pub fn lifespan(&self) -> u64 {
+        self.lifespan_days as u64 * clock::SECONDS_PER_DAY
+    }
+
+    pub fn maturity(&self) -> u64 {
+        self.maturity_days as u64 * clock::SECONDS_PER_DAY
+    }
+
+    pub fn breeding_cooldown(&self) -> u64 {
+        self.breeding_cooldown_days as u64 * clock::SECONDS_PER_DAY
+    }
+
+    pub fn breeds_in(&self, season: Season) -> bool {
+        self.breeding_seasons.contains(&season)
+    }
+
+    /// Chance of a birth among `population` animals of the species, which
+    /// always breed when no carrying capacity is set.
+    pub fn birth_chance(&self, population: u32) -> f64 {
+        self.carrying_capacity
+            .map_or(1.0, |capacity| logistic_birth_chance(population, capacity))
+    }
+
+    pub fn breeding_seasons_str(&self) -> String {
+        self.breeding_seasons
+            .iter()
+            .map(|season| season.as_str())
+            .collect::<Vec<_>>()
+            .join(" and ")
+    }
+
+    /// Stage of an animal `age` seconds after being laid or born.
+    pub fn stage_at(&self, age: u64) -> LifeStage {
+        let incubation = self.incubation_days as u64 * clock::SECONDS_PER_DAY;
+        let youth = self.juvenile_days as u64 * clock::SECONDS_PER_DAY;
+        if age < incubation {
+            LifeStage::Egg
+        } else if age < incubation + youth {
+            if self.class.metamorphoses() {
+                LifeStage::Larva
+            } else {
+                LifeStage::Juvenile
+            }
+        } else {
+            LifeStage::Adult
+        }
+    }
+
+    /// Sex of a newborn, decided by the incubation temperature for species
+    /// with a pivotal temperature and by chance otherwise.
+    pub fn offspring_sex<R: Rng>(&self, temperature: f64, rng: &mut R) -> Sex {
+        match (self.reproduction, self.pivotal_temperature) {
+            (ReproductionMode::Sexual, Some(pivotal_temperature)) => {
+                if rng.gen_bool(pivotal_temperature.warm_sex_ratio(temperature)) {
+                    pivotal_temperature.warm_sex
+                } else {
+                    pivotal_temperature.cold_sex()
+                }
+            }
+            _ => *self.reproduction.offspring_sexes().choose(rng).unwrap(),
+        }
+    }
+
+    /// Sequential hermaphrodites change sex once twice as old as their maturity.
+    pub fn sex_change_age(&self) -> u64 {
+        2 * self.maturity()
+    }
+
+    /// Chance of dying of old age while ageing from `age_from` to `age_to`
+    /// seconds. Nobody dies before reaching the lifespan, past it the risk
+    /// grows exponentially. Risks too large to compute, as for a species
+    /// without lifespan, are certain death.
+    pub fn old_age_death_probability(&self, age_from: u64, age_to: u64) -> f64 {
+        let lifespan = self.lifespan() as f64;
+        let cumulative_hazard = |age: u64| {
+            let overage = (age as f64 / lifespan - 1.0).max(0.0);
+            OLD_AGE_HAZARD / OLD_AGE_HAZARD_GROWTH * ((OLD_AGE_HAZARD_GROWTH * overage).exp() - 1.0)
+        };
+        let probability = 1.0 - (cumulative_hazard(age_from) - cumulative_hazard(age_to)).exp();
+        if probability.is_finite() {
+            probability.clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
 }
 
 pub mod animals_data_models {
@@ -101,13 +467,26 @@ pub mod animals_data_models {
     }
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct AnimalAlive {
-    name: String,
-    sex: Sex,
+    pub(crate) id: u32,
+    pub(crate) name: String,
+    pub(crate) sex: Sex,
+    pub(crate) energy: i64,
+    pub(crate) energy_at: u64,
+    pub(crate) born_at: u64,
+    pub(crate) bred_at: Option<u64>,
+    pub(crate) genotype: Genotype,
+    pub(crate) stage: LifeStage,
+    pub(crate) habitat: String,
+    pub(crate) health: Health,
 }
 
 impl AnimalAlive {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
     pub fn name(&self) -> String {
         self.name.clone()
     }
@@ -117,31 +496,344 @@ impl AnimalAlive {
     }
 
     pub fn sex_str(&self) -> String {
-        let sex = match &self.sex() {
-            Sex::Male => "male",
-            Sex::Female => "female",
-        };
-        sex.to_string()
+        self.sex.as_str().to_string()
+    }
+
+    pub fn energy(&self) -> i64 {
+        self.energy
+    }
+
+    pub fn energy_at(&self) -> u64 {
+        self.energy_at
+    }
+
+    pub fn born_at(&self) -> u64 {
+        self.born_at
+    }
+
+    pub fn age(&self, now: u64) -> u64 {
+        now.saturating_sub(self.born_at)
+    }
+
+    pub fn genotype(&self) -> &Genotype {
+        &self.genotype
+    }
+
+    pub fn habitat(&self) -> &str {
+        &self.habitat
+    }
+
+    pub fn health(&self) -> Health {
+        self.health
+    }
+
+    pub fn stage(&self) -> LifeStage {
+        self.stage
+    }
+
+    pub fn is_adult(&self) -> bool {
+        self.stage == LifeStage::Adult
+    }
+
+    /// Moves the animal on to the stage its age calls for, returning the
+    /// stage it left if it changed. Animals never go back to earlier stages,
+    /// so spawned adults stay adults.
+    pub fn grow_up(&mut self, animal_data: &AnimalData, now: u64) -> Option<LifeStage> {
+        let stage = animal_data.stage_at(self.age(now));
+        if stage > self.stage {
+            let previous_stage = self.stage;
+            self.stage = stage;
+            if previous_stage == LifeStage::Egg {
+                // Hatchlings start with the yolk's energy.
+                self.fed(now);
+            }
+            Some(previous_stage)
+        } else {
+            None
+        }
+    }
+
+    pub fn bred_at(&self) -> Option<u64> {
+        self.bred_at
+    }
+
+    pub fn is_mature(&self, animal_data: &AnimalData, now: u64) -> bool {
+        self.age(now) >= animal_data.maturity()
+    }
+
+    /// Time left before the animal has rested enough to breed again.
+    pub fn breeding_rest_left(&self, animal_data: &AnimalData, now: u64) -> u64 {
+        self.bred_at.map_or(0, |bred_at| {
+            (bred_at + animal_data.breeding_cooldown()).saturating_sub(now)
+        })
+    }
+
+    pub fn bred(&mut self, now: u64) {
+        self.bred_at = Some(now);
+    }
+
+    /// Switches sex if the animal is a sequential hermaphrodite old enough to
+    /// do so, returning whether it did.
+    pub fn change_sex_if_due(&mut self, animal_data: &AnimalData, now: u64) -> bool {
+        match animal_data.reproduction.sex_change() {
+            Some((from, to))
+                if self.sex == from && self.age(now) >= animal_data.sex_change_age() =>
+            {
+                self.sex = to;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Energy left at `now`, after burning calories since the last meal.
+    pub fn current_energy(&self, blood_type: &BloodType, now: u64) -> i64 {
+        let hours = clock::hours_between(self.energy_at, now) as i64;
+        self.energy - blood_type.energy_loss_per_day() * hours / 24
+    }
+
+    pub fn is_hungry(&self, blood_type: &BloodType, now: u64) -> bool {
+        self.current_energy(blood_type, now) < HUNGRY_ENERGY
+    }
+
+    pub fn is_starved(&self, blood_type: &BloodType, now: u64) -> bool {
+        self.current_energy(blood_type, now) < 0
+    }
+
+    pub fn fed(&mut self, now: u64) {
+        self.energy = MAX_ENERGY;
+        self.energy_at = now;
+    }
+
+    pub fn lose_energy(&mut self, amount: i64, blood_type: &BloodType, now: u64) {
+        self.energy = self.current_energy(blood_type, now) - amount;
+        self.energy_at = now;
     }
 
-    pub fn born(name: &str, sex: Sex) -> AnimalAlive {
+    pub fn born(id: u32, name: &str, sex: Sex) -> AnimalAlive {
+        let now = clock::now();
         AnimalAlive {
+            id,
             name: name.to_string(),
             sex,
+            energy: MAX_ENERGY,
+            energy_at: now,
+            born_at: now,
+            bred_at: None,
+            genotype: Genotype::default(),
+            stage: LifeStage::Adult,
+            habitat: String::new(),
+            health: Health::Susceptible,
+        }
+    }
+}
+
+/// Enclosure animals alive are kept in.
+#[derive(PartialEq, Debug)]
+pub struct Habitat {
+    pub name: String,
+    pub biome: String,
+    pub aquatic: bool,
+    pub capacity: u32,
+}
+
+impl Habitat {
+    pub fn environment_str(&self) -> &'static str {
+        if self.aquatic {
+            "aquatic"
+        } else {
+            "land"
+        }
+    }
+}
+
+/// Where an animal stands in an outbreak: it can catch the disease, has it,
+/// or got over it and is immune.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Health {
+    Susceptible,
+    Infected,
+    Recovered,
+}
+
+impl Health {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Health::Susceptible => "susceptible",
+            Health::Infected => "infected",
+            Health::Recovered => "recovered",
+        }
+    }
+}
+
+/// Animals an environmental event falls upon.
+#[derive(PartialEq, Debug, Clone)]
+pub enum EventTarget {
+    All,
+    Class(Class),
+    BloodType(BloodType),
+    Biome(String),
+    Habitat(String),
+    Species(String),
+}
+
+impl EventTarget {
+    pub fn matches(&self, animal: &AnimalAlive, animal_data: &AnimalData, biome: &str) -> bool {
+        match self {
+            EventTarget::All => true,
+            EventTarget::Class(class) => &animal_data.class == class,
+            EventTarget::BloodType(blood_type) => &animal_data.blood_type() == blood_type,
+            EventTarget::Biome(name) => biome == name,
+            EventTarget::Habitat(name) => animal.habitat() == name,
+            EventTarget::Species(name) => &animal.name == name,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum EventEffect {
+    Nothing,
+    /// Each animal targeted dies with this chance.
+    Kill(f64),
+    /// Each animal targeted loses this much energy.
+    Starve(i64),
+    /// This many animals of the targeted species move in.
+    Migrate(u32),
+}
+
+/// Entry of the table environmental events are rolled from, more likely the
+/// heavier its weight.
+#[derive(PartialEq, Debug, Clone)]
+pub struct EnvironmentalEvent {
+    pub name: String,
+    pub weight: u32,
+    pub target: EventTarget,
+    pub effect: EventEffect,
+}
+
+/// Environmental event that happened, with how many animals it touched.
+#[derive(PartialEq, Debug)]
+pub struct AppliedEvent {
+    pub name: String,
+    pub applied_at: u64,
+    pub affected: u32,
+    pub summary: String,
+}
+
+/// Placement of an animal in a habitat, from nowhere when it was born there.
+#[derive(PartialEq, Debug)]
+pub struct Transfer {
+    pub id: u32,
+    pub from: Option<String>,
+    pub to: String,
+    pub reason: String,
+    pub moved_at: u64,
+}
+
+impl Transfer {
+    pub fn describe(&self) -> String {
+        match &self.from {
+            Some(from) => format!(
+                "{}: moved from {} to {} ({})",
+                clock::format_date(self.moved_at),
+                from,
+                self.to,
+                self.reason
+            ),
+            None => format!(
+                "{}: {} in {}",
+                clock::format_date(self.moved_at),
+                self.reason,
+                self.to
+            ),
+        }
+    }
+}
+
+/// Something a command did to the ecosystem, kept in the journal. The
+/// individual is missing for changes to a whole species or to no animal.
+/// When a species or animal row changed, its CSV fields before and after
+/// are kept too, missing when there was no such row.
+#[derive(PartialEq, Debug, Clone)]
+pub struct JournalEntry {
+    pub recorded_at: u64,
+    pub operation: u64,
+    pub command: String,
+    pub species: String,
+    pub individual: Option<u32>,
+    pub outcome: String,
+    pub cause: String,
+    pub before: Option<Vec<String>>,
+    pub after: Option<Vec<String>>,
+}
+
+impl JournalEntry {
+    pub fn changes_row(&self) -> bool {
+        self.before.is_some() || self.after.is_some()
+    }
+
+    pub fn describe(&self) -> String {
+        let mut subject = self.species.clone();
+        if let Some(id) = self.individual {
+            subject = format!("{} #{}", subject, id);
+        }
+        let mut line = format!(
+            "{} [{}] {}",
+            clock::format_date(self.recorded_at),
+            self.command,
+            self.outcome
+        );
+        if !subject.is_empty() {
+            line = format!("{}: {}", line, subject);
+        }
+        if !self.cause.is_empty() {
+            line = format!("{} ({})", line, self.cause);
+        }
+        line
+    }
+}
+
+/// Every animal ever born, kept after its death to trace genealogies.
+#[derive(PartialEq, Debug)]
+pub struct StudbookEntry {
+    pub id: u32,
+    pub name: String,
+    pub sex: Sex,
+    pub mother: Option<u32>,
+    pub father: Option<u32>,
+    pub born_at: u64,
+    pub died_at: Option<u64>,
+}
+
+impl StudbookEntry {
+    pub fn registered(animal: &AnimalAlive, mother: Option<u32>, father: Option<u32>) -> Self {
+        StudbookEntry {
+            id: animal.id(),
+            name: animal.name(),
+            sex: *animal.sex(),
+            mother,
+            father,
+            born_at: animal.born_at(),
+            died_at: None,
         }
     }
+
+    pub fn label(&self) -> String {
+        let dead = if self.died_at.is_some() { ", dead" } else { "" };
+        format!("{} #{} ({}{})", self.name, self.id, self.sex.as_str(), dead)
+    }
 }
 
 pub mod animals_alive_models {
     use super::*;
     pub fn snake_female() -> AnimalAlive {
-        AnimalAlive::born("snake", Sex::Female)
+        AnimalAlive::born(1, "snake", Sex::Female)
     }
     pub fn snake_male() -> AnimalAlive {
-        AnimalAlive::born("snake", Sex::Male)
+        AnimalAlive::born(2, "snake", Sex::Male)
     }
     pub fn chameleon_male() -> AnimalAlive {
-        AnimalAlive::born("chameleon", Sex::Male)
+        AnimalAlive::born(3, "chameleon", Sex::Male)
     }
 }
 
@@ -149,8 +841,8 @@ pub mod animals_alive_models {
 mod tests {
 
     use super::*;
-    use tests::animals_alive_models::snake_female;
-    use tests::animals_data_models::snake_data;
+    use tests::animals_alive_models::{snake_female, snake_male};
+    use tests::animals_data_models::{chameleon_data, snake_data};
 
     #[test]
     fn test_class() {
@@ -189,6 +881,124 @@ mod tests {
         assert_eq!(snake_blood_type, BloodType::Cold);
     }
 
+    #[test]
+    fn test_birth_chance() {
+        assert_eq!(logistic_birth_chance(0, 10), 1.0);
+        assert_eq!(logistic_birth_chance(5, 10), 0.5);
+        assert_eq!(logistic_birth_chance(12, 10), 0.0);
+        let mut snake = snake_data();
+        assert_eq!(snake.birth_chance(1000), 1.0);
+        snake.carrying_capacity = Some(40);
+        assert_eq!(snake.birth_chance(30), 0.25);
+    }
+
+    #[test]
+    fn test_hunts() {
+        let snake = snake_data();
+        let chameleon = chameleon_data();
+        assert!(snake.hunts(&chameleon));
+        assert!(!chameleon.hunts(&snake));
+        assert!(!snake.hunts(&snake_data()));
+    }
+
+    #[test]
+    fn test_activity() {
+        assert_eq!(BloodType::Warm.activity(-5.0), 1.0);
+        assert_eq!(BloodType::Cold.activity(5.0), 0.0);
+        assert!((BloodType::Cold.activity(20.0) - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(BloodType::Cold.activity(30.0), 1.0);
+    }
+
+    #[test]
+    fn test_old_age_death_probability() {
+        let snake = snake_data();
+        let lifespan = snake.lifespan();
+        assert_eq!(snake.old_age_death_probability(0, lifespan), 0.0);
+
+        let one_tenth_older = snake.old_age_death_probability(lifespan, lifespan * 11 / 10);
+        assert!(one_tenth_older > 0.5 && one_tenth_older < 0.6);
+        assert!(snake.old_age_death_probability(lifespan, lifespan * 2) > 0.99);
+        assert_eq!(snake.old_age_death_probability(0, lifespan * 1000), 1.0);
+
+        let mut ageless = snake_data();
+        ageless.lifespan_days = 0;
+        assert_eq!(ageless.old_age_death_probability(10, 20), 1.0);
+    }
+
+    #[test]
+    fn test_breeding_readiness() {
+        let snake = snake_data();
+        let mut snake_female = snake_female();
+        let born_at = snake_female.born_at();
+        assert!(!snake_female.is_mature(&snake, born_at));
+        assert!(snake_female.is_mature(&snake, born_at + snake.maturity()));
+
+        assert_eq!(snake_female.breeding_rest_left(&snake, born_at), 0);
+        snake_female.bred(born_at);
+        assert_eq!(
+            snake_female.breeding_rest_left(&snake, born_at + 10),
+            snake.breeding_cooldown() - 10
+        );
+    }
+
+    #[test]
+    fn test_warm_sex_ratio() {
+        let pivotal_temperature = PivotalTemperature {
+            celsius: 29.0,
+            transitional_range: 2.0,
+            warm_sex: Sex::Female,
+        };
+        assert_eq!(pivotal_temperature.warm_sex_ratio(29.0), 0.5);
+        assert!((pivotal_temperature.warm_sex_ratio(30.0) - 0.95).abs() < 1e-9);
+        assert!((pivotal_temperature.warm_sex_ratio(28.0) - 0.05).abs() < 1e-9);
+        assert_eq!(pivotal_temperature.cold_sex(), Sex::Male);
+    }
+
+    #[test]
+    fn test_grow_up() {
+        let mut frog_data = snake_data();
+        frog_data.class = Class::Amphibian;
+        frog_data.incubation_days = 10;
+        frog_data.juvenile_days = 20;
+        let day = clock::SECONDS_PER_DAY;
+        assert_eq!(frog_data.stage_at(9 * day), LifeStage::Egg);
+        assert_eq!(frog_data.stage_at(10 * day), LifeStage::Larva);
+        assert_eq!(frog_data.stage_at(30 * day), LifeStage::Adult);
+        assert_eq!(snake_data().stage_at(100 * day), LifeStage::Juvenile);
+
+        let mut frog = snake_female();
+        let born_at = frog.born_at();
+        frog.stage = LifeStage::Egg;
+        assert_eq!(frog.grow_up(&frog_data, born_at + day), None);
+        assert_eq!(
+            frog.grow_up(&frog_data, born_at + 12 * day),
+            Some(LifeStage::Egg)
+        );
+        assert_eq!(frog.stage(), LifeStage::Larva);
+
+        let mut spawned = snake_male();
+        assert_eq!(spawned.grow_up(&frog_data, born_at), None);
+        assert!(spawned.is_adult());
+    }
+
+    #[test]
+    fn test_change_sex_if_due() {
+        let mut clownfish = snake_data();
+        clownfish.reproduction = ReproductionMode::Protandrous;
+        let mut male = snake_male();
+        let mut female = snake_female();
+        let born_at = male.born_at();
+
+        assert!(!male.change_sex_if_due(&clownfish, born_at));
+        assert_eq!(male.sex(), &Sex::Male);
+
+        let old_enough = born_at + clownfish.sex_change_age();
+        assert!(male.change_sex_if_due(&clownfish, old_enough));
+        assert_eq!(male.sex(), &Sex::Female);
+        assert!(!female.change_sex_if_due(&clownfish, old_enough));
+        assert!(!female.change_sex_if_due(&snake_data(), old_enough));
+    }
+
     #[test]
     fn test_name() {
         let snake = snake_female();
@@ -202,4 +1012,26 @@ mod tests {
         let snake_sex = snake.sex();
         assert_eq!(snake_sex, &Sex::Female);
     }
+
+    #[test]
+    fn test_current_energy() {
+        let mut snake = snake_female();
+        snake.energy_at = 0;
+        let two_days = 2 * clock::SECONDS_PER_DAY;
+        assert_eq!(snake.current_energy(&BloodType::Cold, two_days), 90);
+        assert_eq!(snake.current_energy(&BloodType::Warm, two_days), 60);
+        assert_eq!(
+            snake.current_energy(&BloodType::Warm, 12 * clock::SECONDS_PER_HOUR),
+            90
+        );
+        assert!(!snake.is_hungry(&BloodType::Warm, two_days));
+        assert!(snake.is_hungry(&BloodType::Warm, 2 * two_days));
+        assert!(snake.is_starved(&BloodType::Warm, 3 * two_days));
+
+        snake.fed(3 * two_days);
+        assert_eq!(
+            snake.current_energy(&BloodType::Warm, 3 * two_days),
+            MAX_ENERGY
+        );
+    }
 }
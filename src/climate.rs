@@ -0,0 +1,140 @@
+use std::error::Error;
+use std::f64::consts::TAU;
+use std::fs;
+
+use crate::clock;
+use crate::temp_file_handler::write_file_durably;
+use crate::CLIMATE_FILE_PATH;
+
+pub const DEFAULT_MEAN_TEMPERATURE: f64 = 20.0;
+pub const DEFAULT_SEASONAL_AMPLITUDE: f64 = 8.0;
+
+// Days into the year of the warmest day, mid July.
+const WARMEST_DAY: f64 = 196.0;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub const ALL: [Season; 4] = [
+        Season::Spring,
+        Season::Summer,
+        Season::Autumn,
+        Season::Winter,
+    ];
+
+    /// Season of a timestamp, by its month in the northern hemisphere.
+    pub fn at(timestamp: u64) -> Season {
+        match clock::civil_date(timestamp).1 {
+            3..=5 => Season::Spring,
+            6..=8 => Season::Summer,
+            9..=11 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Season::Spring => "spring",
+            Season::Summer => "summer",
+            Season::Autumn => "autumn",
+            Season::Winter => "winter",
+        }
+    }
+}
+
+/// Yearly temperature cycle, warmest in mid July and coldest in mid January.
+#[derive(PartialEq, Debug)]
+pub struct Climate {
+    pub mean_temperature: f64,
+    pub seasonal_amplitude: f64,
+}
+
+impl Default for Climate {
+    fn default() -> Self {
+        Climate {
+            mean_temperature: DEFAULT_MEAN_TEMPERATURE,
+            seasonal_amplitude: DEFAULT_SEASONAL_AMPLITUDE,
+        }
+    }
+}
+
+impl Climate {
+    pub fn temperature_at(&self, timestamp: u64) -> f64 {
+        let phase = (clock::day_of_year(timestamp) as f64 - WARMEST_DAY) / 365.0;
+        self.mean_temperature + self.seasonal_amplitude * (TAU * phase).cos()
+    }
+
+    pub fn to_text(&self) -> String {
+        format!("{}:{}", self.mean_temperature, self.seasonal_amplitude)
+    }
+
+    pub fn from_text(text: &str) -> Option<Climate> {
+        let (mean_temperature, seasonal_amplitude) = text.trim().split_once(':')?;
+        Some(Climate {
+            mean_temperature: mean_temperature.parse().ok()?,
+            seasonal_amplitude: seasonal_amplitude.parse().ok()?,
+        })
+    }
+}
+
+pub fn climate() -> Climate {
+    fs::read_to_string(CLIMATE_FILE_PATH)
+        .ok()
+        .and_then(|climate| Climate::from_text(&climate))
+        .unwrap_or_default()
+}
+
+pub fn set_climate(climate: &Climate) -> Result<(), Box<dyn Error>> {
+    write_file_durably(CLIMATE_FILE_PATH, &climate.to_text())?;
+    Ok(())
+}
+
+/// Ambient temperature in celsius, where eggs are incubated.
+pub fn temperature() -> f64 {
+    climate().temperature_at(clock::now())
+}
+
+pub fn season() -> Season {
+    Season::at(clock::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-01-15, 2024-04-15 and 2024-07-15 at midnight.
+    const JANUARY: u64 = 1_705_276_800;
+    const APRIL: u64 = 1_713_139_200;
+    const JULY: u64 = 1_721_001_600;
+
+    #[test]
+    fn test_season_at() {
+        assert_eq!(Season::at(JANUARY), Season::Winter);
+        assert_eq!(Season::at(APRIL), Season::Spring);
+        assert_eq!(Season::at(JULY), Season::Summer);
+    }
+
+    #[test]
+    fn test_temperature_at() {
+        let climate = Climate::default();
+        assert!((climate.temperature_at(JULY) - 28.0).abs() < 0.1);
+        assert!((climate.temperature_at(JANUARY) - 12.0).abs() < 0.1);
+        assert!((climate.temperature_at(APRIL) - 20.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_text_roundtrip() {
+        let climate = Climate {
+            mean_temperature: 15.5,
+            seasonal_amplitude: 3.0,
+        };
+        assert_eq!(Climate::from_text(&climate.to_text()), Some(climate));
+        assert_eq!(Climate::from_text("25"), None);
+    }
+}
@@ -0,0 +1,198 @@
+use std::error::Error;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::temp_file_handler::write_file_durably;
+use crate::CLOCK_FILE_PATH;
+
+pub const SECONDS_PER_HOUR: u64 = 60 * 60;
+pub const SECONDS_PER_DAY: u64 = 24 * SECONDS_PER_HOUR;
+pub const SECONDS_PER_WEEK: u64 = 7 * SECONDS_PER_DAY;
+pub const SECONDS_PER_YEAR: u64 = 365 * SECONDS_PER_DAY;
+
+/// Seconds the ecosystem has been pushed forward with `advance-time`.
+fn offset() -> u64 {
+    fs::read_to_string(CLOCK_FILE_PATH)
+        .ok()
+        .and_then(|offset| offset.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Current ecosystem time: wall clock plus every advance made so far.
+pub fn now() -> u64 {
+    let wall_clock = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    wall_clock.saturating_add(offset())
+}
+
+pub fn advance(seconds: u64) -> Result<(), Box<dyn Error>> {
+    let offset = offset()
+        .checked_add(seconds)
+        .ok_or("The ecosystem clock can't go that far")?;
+    write_file_durably(CLOCK_FILE_PATH, &offset.to_string())?;
+    Ok(())
+}
+
+pub fn hours_between(from: u64, to: u64) -> u64 {
+    to.saturating_sub(from) / SECONDS_PER_HOUR
+}
+
+pub fn format_duration(seconds: u64) -> String {
+    let years = seconds / SECONDS_PER_YEAR;
+    let days = seconds % SECONDS_PER_YEAR / SECONDS_PER_DAY;
+    let hours = seconds % SECONDS_PER_DAY / SECONDS_PER_HOUR;
+    match (years, days) {
+        (0, 0) => format!("{}h", hours),
+        (0, _) => format!("{}d {}h", days, hours),
+        _ => format!("{}y {}d", years, days),
+    }
+}
+
+/// Calendar year, month and day of a timestamp.
+pub fn civil_date(timestamp: u64) -> (i64, u32, u32) {
+    // Howard Hinnant's days-to-civil algorithm, counting eras from 0000-03-01.
+    let days = (timestamp / SECONDS_PER_DAY) as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month as u32, day as u32)
+}
+
+/// Timestamp of the midnight starting a `YYYY-MM-DD` date.
+pub fn parse_date(date: &str) -> Option<u64> {
+    let mut parts = date.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: i64 = parts.next()?.parse().ok()?;
+    let day: i64 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    // Inverse of `civil_date`, with years starting in March.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    let timestamp = u64::try_from(days).ok()?.checked_mul(SECONDS_PER_DAY)?;
+    // Days past the end of their month, like 2026-02-30, would roll over.
+    let (_, parsed_month, parsed_day) = civil_date(timestamp);
+    (i64::from(parsed_month) == month && i64::from(parsed_day) == day).then_some(timestamp)
+}
+
+/// Timestamp of a `YYYY-MM-DD` date's midnight, or of a time within it
+/// given as `YYYY-MM-DDTHH:MM` or `YYYY-MM-DDTHH:MM:SS`.
+pub fn parse_time(time: &str) -> Option<u64> {
+    let (date, time_of_day) = match time.split_once('T') {
+        Some((date, time_of_day)) => (date, time_of_day),
+        None => (time, "00:00"),
+    };
+    let mut parts = time_of_day.split(':');
+    let hours: u64 = parts.next()?.parse().ok()?;
+    let minutes: u64 = parts.next()?.parse().ok()?;
+    let seconds: u64 = parts
+        .next()
+        .map_or(Some(0), |seconds| seconds.parse().ok())?;
+    if parts.next().is_some() || hours > 23 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+    Some(parse_date(date)? + hours * SECONDS_PER_HOUR + minutes * 60 + seconds)
+}
+
+/// Day of the year of a timestamp, from 1, ignoring leap days.
+pub fn day_of_year(timestamp: u64) -> u32 {
+    const DAYS_BEFORE_MONTH: [u32; 12] = [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334];
+    let (_, month, day) = civil_date(timestamp);
+    DAYS_BEFORE_MONTH[month as usize - 1] + day
+}
+
+/// Calendar date of a timestamp, as `YYYY-MM-DD`.
+pub fn format_date(timestamp: u64) -> String {
+    let (year, month, day) = civil_date(timestamp);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Time of a timestamp, as `YYYY-MM-DDTHH:MM:SS`, which `parse_time` reads.
+pub fn format_time(timestamp: u64) -> String {
+    let seconds = timestamp % SECONDS_PER_DAY;
+    format!(
+        "{}T{:02}:{:02}:{:02}",
+        format_date(timestamp),
+        seconds / SECONDS_PER_HOUR,
+        seconds % SECONDS_PER_HOUR / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hours_between() {
+        assert_eq!(hours_between(0, 3 * SECONDS_PER_HOUR), 3);
+        assert_eq!(hours_between(0, SECONDS_PER_HOUR - 1), 0);
+        assert_eq!(hours_between(10, 0), 0);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(5 * SECONDS_PER_HOUR), "5h");
+        assert_eq!(
+            format_duration(3 * SECONDS_PER_DAY + SECONDS_PER_HOUR),
+            "3d 1h"
+        );
+        assert_eq!(
+            format_duration(2 * SECONDS_PER_YEAR + 4 * SECONDS_PER_DAY),
+            "2y 4d"
+        );
+    }
+
+    #[test]
+    fn test_calendar() {
+        // 2024-03-01, just after a leap day.
+        let timestamp = 1_709_251_200;
+        assert_eq!(civil_date(timestamp), (2024, 3, 1));
+        assert_eq!(format_date(timestamp), "2024-03-01");
+        assert_eq!(day_of_year(timestamp), 60);
+        assert_eq!(parse_date("2024-03-01"), Some(timestamp));
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+        assert_eq!(parse_date("2024-13-01"), None);
+        assert_eq!(parse_date("1969-12-31"), None);
+        assert_eq!(parse_date("2026-02-30"), None);
+        assert_eq!(parse_date("2024-02-29"), Some(timestamp - SECONDS_PER_DAY));
+        assert_eq!(parse_time("2024-03-01"), Some(timestamp));
+        assert_eq!(
+            parse_time("2024-03-01T09:30"),
+            Some(timestamp + 9 * SECONDS_PER_HOUR + 1800)
+        );
+        assert_eq!(
+            parse_time("2024-03-01T23:59:59"),
+            Some(timestamp + SECONDS_PER_DAY - 1)
+        );
+        assert_eq!(
+            format_time(timestamp + 9 * SECONDS_PER_HOUR + 5),
+            "2024-03-01T09:00:05"
+        );
+        assert_eq!(
+            parse_time("2024-03-01T09:00:05"),
+            Some(timestamp + 9 * SECONDS_PER_HOUR + 5)
+        );
+        assert_eq!(parse_time("2024-03-01T24:00"), None);
+        assert_eq!(parse_time("2024-03-01T09"), None);
+        assert_eq!(parse_time("2024-03-01T09:30:00:00"), None);
+    }
+}
@@ -0,0 +1,53 @@
+use std::error::Error;
+use std::fs;
+
+use crate::animal_structs::AnimalData;
+use crate::temp_file_handler::write_file_durably;
+use crate::COHABITATION_FILE_PATH;
+
+/// Whether placements putting a predator with its prey are refused rather
+/// than only warned about.
+pub fn is_strict() -> bool {
+    fs::read_to_string(COHABITATION_FILE_PATH).is_ok_and(|mode| mode.trim() == "strict")
+}
+
+pub fn set_strict(strict: bool) -> Result<(), Box<dyn Error>> {
+    write_file_durably(
+        COHABITATION_FILE_PATH,
+        if strict { "strict" } else { "warn" },
+    )?;
+    Ok(())
+}
+
+/// Every predator and prey among species living together, by name.
+pub fn predator_prey_pairs(species: &[&AnimalData]) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    for predator in species {
+        for prey in species {
+            if predator.hunts(prey) {
+                pairs.push((predator.name(), prey.name()));
+            }
+        }
+    }
+    pairs.sort();
+    pairs.dedup();
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::animal_structs::animals_data_models::{chameleon_data, snake_data};
+
+    #[test]
+    fn test_predator_prey_pairs() {
+        let snake = snake_data();
+        let chameleon = chameleon_data();
+        assert_eq!(
+            predator_prey_pairs(&[&chameleon, &snake]),
+            vec![("snake".to_string(), "chameleon".to_string())]
+        );
+        assert!(predator_prey_pairs(&[&snake]).is_empty());
+    }
+}
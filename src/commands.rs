@@ -1,14 +1,44 @@
-use rand::prelude::{thread_rng, SliceRandom};
+use csv::StringRecord;
+use rand::prelude::{thread_rng, Rng, SliceRandom};
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
 
 use crate::animal_alive_csv::{
-    kill_all_animals_alive, kill_one_animal_alive, reader_animals_alive, writer_animals_alive,
-    AnimalAliveReader,
+    append_animals_alive, kill_all_animals_alive, kill_animals_alive_by_id, reader_animals_alive,
+    revive_animal_alive, update_animal_alive, AnimalAliveReader, ANIMALS_ALIVE_HEADERS,
 };
 use crate::animal_data_csv::{
-    delete_animal_data, reader_animals_data, writer_animals_data, AnimalDataReader,
+    append_animal_data, delete_animal_data, reader_animals_data, AnimalDataReader,
+    ANIMALS_DATA_HEADERS,
 };
 use crate::animal_structs::*;
+use crate::climate;
+use crate::clock;
+use crate::cohabitation;
+use crate::custom_writers_and_readers::create_writer_truncate_for_path;
+use crate::disease;
+use crate::environmental_events_csv::{
+    event_effect_to_csv, event_target_to_csv, reader_environmental_events,
+    reader_environmental_events_log, roll_event, writer_environmental_events_log,
+    EnvironmentalEventsReader,
+};
+use crate::file_lock;
+use crate::genetics::{allele_frequencies, Genotype};
+use crate::grid::Grid;
+use crate::habitats_csv::{append_habitat, reader_habitats, HabitatReader};
+use crate::journal_csv::{
+    append_journal_entries, reader_journal, writer_journal, JournalQuery, JournalReader,
+};
+use crate::pedigree::Pedigree;
+use crate::replay::Projection;
+use crate::studbook_csv::{
+    export_studbook, reader_studbook, record_deaths, record_revivals, register_births,
+    StudbookReader,
+};
+use crate::transaction::Transaction;
+use crate::transfers_csv::{log_transfers, reader_transfers, TransferReader};
+use crate::undo;
+use crate::{REPLAYED_ANIMALS_ALIVE_FILE_PATH, REPLAYED_ANIMALS_DATA_FILE_PATH};
 
 pub fn animal_data_registered(animal_name: &str) -> Result<bool, Box<dyn Error>> {
     let mut reader = reader_animals_data()?;
@@ -33,74 +63,878 @@ pub fn create_animal(animal: AnimalData) -> Result<String, Box<dyn Error>> {
     if animal_data_registered(&animal_name)? {
         Ok(format!("{} already exists", animal_name))
     } else {
-        let mut writer = writer_animals_data()?;
-        writer.write_animal_data(&animal)?;
+        let mut transaction = Transaction::new();
+        append_animal_data(&mut transaction, &animal)?;
+        let entry = journal_entry(
+            "create",
+            &animal_name,
+            None,
+            "created",
+            "",
+            None,
+            Some(animal.to_csv().to_vec()),
+        );
+        append_journal_entries(&mut transaction, &[entry])?;
+        transaction.commit()?;
         Ok(format!("{} data added", animal_name))
     }
 }
 
+fn animals_alive_of(animal_name: &str) -> Result<Vec<AnimalAlive>, Box<dyn Error>> {
+    Ok(reader_animals_alive()?
+        .read_animals_alive()?
+        .into_iter()
+        .filter(|animal| animal.name() == animal_name)
+        .collect())
+}
+
+/// What a command did, with the species or animal row it changed before
+/// and after if it changed one.
+fn journal_entry(
+    command: &str,
+    species: &str,
+    individual: Option<u32>,
+    outcome: &str,
+    cause: &str,
+    before: Option<Vec<String>>,
+    after: Option<Vec<String>>,
+) -> JournalEntry {
+    JournalEntry {
+        recorded_at: clock::now(),
+        operation: undo::current_operation(),
+        command: command.to_string(),
+        species: species.to_string(),
+        individual,
+        outcome: outcome.to_string(),
+        cause: cause.to_string(),
+        before,
+        after,
+    }
+}
+
+/// Appends what a command did to the journal.
+fn journal_change(
+    command: &str,
+    species: &str,
+    individual: Option<u32>,
+    outcome: &str,
+    cause: &str,
+    before: Option<Vec<String>>,
+    after: Option<Vec<String>>,
+) -> Result<(), Box<dyn Error>> {
+    writer_journal()?.write_journal_entry(&journal_entry(
+        command, species, individual, outcome, cause, before, after,
+    ))
+}
+
+fn journal(
+    command: &str,
+    species: &str,
+    individual: Option<u32>,
+    outcome: &str,
+    cause: &str,
+) -> Result<(), Box<dyn Error>> {
+    journal_change(command, species, individual, outcome, cause, None, None)
+}
+
+/// Saves the changes made to an animal alive and journals them in one
+/// transaction, under the exclusive lock commands hold from reading the
+/// animal to saving it.
+fn save_animal(
+    animal: &AnimalAlive,
+    command: &str,
+    outcome: &str,
+    cause: &str,
+) -> Result<(), Box<dyn Error>> {
+    let _lock = file_lock::FileLock::acquire(file_lock::LockMode::Exclusive)?;
+    let mut transaction = Transaction::new();
+    stage_animal(&mut transaction, animal, command, outcome, cause)?;
+    transaction.commit()
+}
+
+/// Stages the changes made to an animal alive along with their journal
+/// entry, for commands changing more files with it.
+fn stage_animal(
+    transaction: &mut Transaction,
+    animal: &AnimalAlive,
+    command: &str,
+    outcome: &str,
+    cause: &str,
+) -> Result<(), Box<dyn Error>> {
+    let before = update_animal_alive(transaction, animal)?;
+    let entry = journal_entry(
+        command,
+        &animal.name(),
+        Some(animal.id()),
+        outcome,
+        cause,
+        before.map(|before| before.to_csv().to_vec()),
+        Some(animal.to_csv().to_vec()),
+    );
+    append_journal_entries(transaction, &[entry])
+}
+
+/// Removes dead animals from the living ones, keeping them in the studbook.
+fn bury_animals(animals: &[AnimalAlive], command: &str, cause: &str) -> Result<(), Box<dyn Error>> {
+    if animals.is_empty() {
+        return Ok(());
+    }
+    let ids = animals.iter().map(|animal| animal.id()).collect::<Vec<_>>();
+    let mut transaction = Transaction::new();
+    let buried = kill_animals_alive_by_id(&mut transaction, &ids)?;
+    record_deaths(&mut transaction, &ids, clock::now())?;
+    let entries = buried
+        .iter()
+        .map(|animal| {
+            journal_entry(
+                command,
+                &animal.name(),
+                Some(animal.id()),
+                "died",
+                cause,
+                Some(animal.to_csv().to_vec()),
+                None,
+            )
+        })
+        .collect::<Vec<_>>();
+    append_journal_entries(&mut transaction, &entries)?;
+    transaction.commit()
+}
+
 pub fn delete_animal(animal_name: String) -> Result<String, Box<dyn Error>> {
     let animal_name = animal_name.as_str();
-    delete_animal_data(animal_name)?;
-    kill_all_animals_alive(animal_name)?;
+    let animal_data = reader_animals_data()?.read_animal_data(animal_name)?;
+    let animals = animals_alive_of(animal_name)?;
+    let ids = animals.iter().map(|animal| animal.id()).collect::<Vec<_>>();
+    let mut transaction = Transaction::new();
+    delete_animal_data(&mut transaction, animal_name)?;
+    kill_all_animals_alive(&mut transaction, animal_name)?;
+    record_deaths(&mut transaction, &ids, clock::now())?;
+    let mut entries = animals
+        .iter()
+        .map(|animal| {
+            journal_entry(
+                "delete",
+                animal_name,
+                Some(animal.id()),
+                "died",
+                "species deleted",
+                Some(animal.to_csv().to_vec()),
+                None,
+            )
+        })
+        .collect::<Vec<_>>();
+    entries.push(journal_entry(
+        "delete",
+        animal_name,
+        None,
+        "deleted",
+        "",
+        animal_data.map(|animal_data| animal_data.to_csv().to_vec()),
+        None,
+    ));
+    append_journal_entries(&mut transaction, &entries)?;
+    transaction.commit()?;
     Ok(format!("{} is now extinct!", animal_name))
 }
 
+fn describe_animal_data(animal_data: &AnimalData) -> String {
+    format!(
+        "Name: {}\nClass: {}\nPredators: {:?}\nPreys: {:?}",
+        animal_data.name,
+        animal_data.class_str(),
+        animal_data.predators,
+        animal_data.preys
+    )
+}
+
 pub fn read_animal(animal_name: String) -> Result<String, Box<dyn Error>> {
     let mut reader = reader_animals_data()?;
 
     if let Some(animal_data) = reader.read_animal_data(animal_name.as_str())? {
-        Ok(format!(
-            "Name: {}\nClass: {}\nPredators: {:?}\nPreys: {:?}",
-            animal_data.name,
-            animal_data.class_str(),
-            animal_data.predators,
-            animal_data.preys
-        ))
+        Ok(describe_animal_data(&animal_data))
     } else {
         Ok("Animal not found".to_string())
     }
 }
-pub fn spawn_animal(animal_name: String, animal_sex: Sex) -> Result<String, Box<dyn Error>> {
-    if !animal_data_registered(&animal_name)? {
-        return Ok(format!("{} does not exist", animal_name));
+
+/// When the journal's first entry was recorded, if it has any.
+pub fn journal_start() -> Result<Option<u64>, Box<dyn Error>> {
+    let entries = reader_journal()?.read_journal(&JournalQuery::default())?;
+    Ok(entries.iter().map(|entry| entry.recorded_at).min())
+}
+
+/// Species and animals alive at a time, as the journal tells them. Times
+/// before the journal started are an error, the journal knowing nothing
+/// of them.
+fn projection_at(time: &str) -> Result<Projection, Box<dyn Error>> {
+    let at = clock::parse_time(time).ok_or("Invalid time")?;
+    match journal_start()? {
+        Some(start) if start <= at => {}
+        Some(start) => {
+            return Err(format!(
+                "The journal starts at {}, after {}",
+                clock::format_time(start),
+                time
+            )
+            .into())
+        }
+        None => return Err("The journal is empty".into()),
     }
-    let mut writer = writer_animals_alive()?;
+    let query = JournalQuery {
+        to: Some(at + 1),
+        ..Default::default()
+    };
+    Ok(Projection::from_journal(
+        &reader_journal()?.read_journal(&query)?,
+    ))
+}
 
-    let animal_name = animal_name.as_str();
-    let animal = AnimalAlive::born(animal_name, animal_sex);
-    let animal_sex = animal.sex_str();
-    writer.write_animal_alive(animal)?;
+pub fn read_animal_at(animal_name: String, time: String) -> Result<String, Box<dyn Error>> {
+    let projection = projection_at(&time)?;
+    match projection.species.get(&animal_name) {
+        Some(row) => Ok(describe_animal_data(&AnimalData::from_csv(
+            &StringRecord::from(row.clone()),
+        )?)),
+        None => Ok(format!("{} did not exist at {}", animal_name, time)),
+    }
+}
+pub fn create_habitat(habitat: Habitat) -> Result<String, Box<dyn Error>> {
+    if reader_habitats()?.read_habitat(&habitat.name)?.is_some() {
+        Ok(format!("{} habitat already exists", habitat.name))
+    } else {
+        let mut transaction = Transaction::new();
+        append_habitat(&mut transaction, &habitat)?;
+        let entry = journal_entry(
+            "create-habitat",
+            "",
+            None,
+            "habitat-created",
+            &format!("{} in {}", habitat.name, habitat.biome),
+            None,
+            None,
+        );
+        append_journal_entries(&mut transaction, &[entry])?;
+        transaction.commit()?;
+        Ok(format!("{} habitat added", habitat.name))
+    }
+}
+
+fn animals_alive_in(habitat_name: &str) -> Result<Vec<AnimalAlive>, Box<dyn Error>> {
+    Ok(reader_animals_alive()?
+        .read_animals_alive()?
+        .into_iter()
+        .filter(|animal| animal.habitat() == habitat_name)
+        .collect())
+}
+
+pub fn habitats() -> Result<String, Box<dyn Error>> {
+    let habitats = reader_habitats()?.read_habitats()?;
+    if habitats.is_empty() {
+        return Ok("There are no habitats yet".to_string());
+    }
+    let mut report = Vec::new();
+    for habitat in habitats {
+        let residents = animals_alive_in(&habitat.name)?;
+        let mut species = residents
+            .iter()
+            .map(|animal| animal.name())
+            .collect::<Vec<_>>();
+        species.sort();
+        species.dedup();
+        report.push(format!(
+            "{} ({}, {}): {}/{}{}",
+            habitat.name,
+            habitat.biome,
+            habitat.environment_str(),
+            residents.len(),
+            habitat.capacity,
+            if species.is_empty() {
+                String::new()
+            } else {
+                format!(", {}", species.join(", "))
+            }
+        ));
+    }
+    Ok(report.join("\n"))
+}
+
+/// Why `newcomers` animals of a species can't be placed in a habitat, if
+/// they can't.
+fn placement_problem(
+    habitat_name: &str,
+    animal_data: &AnimalData,
+    newcomers: u32,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let Some(habitat) = reader_habitats()?.read_habitat(habitat_name)? else {
+        return Ok(Some(format!("There is no {} habitat", habitat_name)));
+    };
+    if animal_data.class.lives_in_water() && !habitat.aquatic {
+        return Ok(Some(format!(
+            "A {} can't live on land in {}",
+            animal_data.name, habitat.name
+        )));
+    }
+    let residents = animals_alive_in(habitat_name)?.len() as u32;
+    if residents + newcomers > habitat.capacity {
+        return Ok(Some(format!(
+            "{} is full ({}/{}), there is no room for {} more",
+            habitat.name, residents, habitat.capacity, newcomers
+        )));
+    }
+    Ok(None)
+}
+
+/// How many more animals fit in a habitat.
+fn room_left(habitat_name: &str) -> Result<u32, Box<dyn Error>> {
+    let Some(habitat) = reader_habitats()?.read_habitat(habitat_name)? else {
+        return Ok(0);
+    };
+    let residents = animals_alive_in(habitat_name)?.len() as u32;
+    Ok(habitat.capacity.saturating_sub(residents))
+}
+
+/// Warning about a species moving in with its predators or preys, if it would.
+fn cohabitation_problem(
+    habitat_name: &str,
+    animal_data: &AnimalData,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let animals_data = animals_data_by_name()?;
+    let mut problems = Vec::new();
+    for resident in animals_alive_in(habitat_name)? {
+        let Some(resident_data) = animals_data.get(&resident.name()) else {
+            continue;
+        };
+        let problem = if animal_data.hunts(resident_data) {
+            format!(
+                "a {} would eat the {}s",
+                animal_data.name, resident_data.name
+            )
+        } else if resident_data.hunts(animal_data) {
+            format!(
+                "the {}s would eat a {}",
+                resident_data.name, animal_data.name
+            )
+        } else {
+            continue;
+        };
+        if !problems.contains(&problem) {
+            problems.push(problem);
+        }
+    }
+    if problems.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(format!(
+            "In {}, {}",
+            habitat_name,
+            problems.join(" and ")
+        )))
+    }
+}
+
+pub fn check_enclosures() -> Result<String, Box<dyn Error>> {
+    let animals_data = animals_data_by_name()?;
+    let mut report = Vec::new();
+    for habitat in reader_habitats()?.read_habitats()? {
+        let mut species = Vec::new();
+        for resident in animals_alive_in(&habitat.name)? {
+            if let Some(resident_data) = animals_data.get(&resident.name()) {
+                species.push(resident_data);
+            }
+        }
+        for (predator, prey) in cohabitation::predator_prey_pairs(&species) {
+            report.push(format!(
+                "{}: {} lives with its prey, {}",
+                habitat.name, predator, prey
+            ));
+        }
+    }
+    if report.is_empty() {
+        Ok("No predator lives with its prey".to_string())
+    } else {
+        Ok(report.join("\n"))
+    }
+}
+
+pub fn set_cohabitation(mode: &str) -> Result<String, Box<dyn Error>> {
+    let strict = mode == "strict";
+    cohabitation::set_strict(strict)?;
+    journal("set-cohabitation", "", None, "cohabitation-set", mode)?;
+    if strict {
+        Ok("Predators won't be placed with their preys anymore".to_string())
+    } else {
+        Ok("Placing predators with their preys only warns now".to_string())
+    }
+}
+
+pub fn set_lock_timeout(seconds: u64) -> Result<String, Box<dyn Error>> {
+    file_lock::set_lock_timeout(seconds)?;
     Ok(format!(
-        "A {} has borned! It's a {}",
-        animal_name, animal_sex
+        "Commands now wait up to {}s for other terminals to finish",
+        seconds
     ))
 }
 
+/// Brings newborns of the same parents to life, recording them in the
+/// studbook, the transfers and the journal all at once.
+fn give_birth(
+    newborns: &[AnimalAlive],
+    mother: Option<u32>,
+    father: Option<u32>,
+    command: &str,
+) -> Result<(), Box<dyn Error>> {
+    let parents = match (mother, father) {
+        (Some(mother), Some(father)) => format!("of #{} and #{}", mother, father),
+        (Some(mother), None) => format!("of #{}", mother),
+        _ => "spawned".to_string(),
+    };
+    let mut entries = Vec::new();
+    let mut studbook_entries = Vec::new();
+    let mut transfers = Vec::new();
+    for animal in newborns {
+        entries.push(journal_entry(
+            command,
+            &animal.name(),
+            Some(animal.id()),
+            "born",
+            &format!("{} in {}", parents, animal.habitat()),
+            None,
+            Some(animal.to_csv().to_vec()),
+        ));
+        studbook_entries.push(StudbookEntry::registered(animal, mother, father));
+        transfers.push(Transfer {
+            id: animal.id(),
+            from: None,
+            to: animal.habitat().to_string(),
+            reason: if mother.is_some() { "born" } else { "spawned" }.to_string(),
+            moved_at: animal.born_at(),
+        });
+    }
+    let mut transaction = Transaction::new();
+    append_animals_alive(&mut transaction, newborns)?;
+    register_births(&mut transaction, &studbook_entries)?;
+    log_transfers(&mut transaction, &transfers)?;
+    append_journal_entries(&mut transaction, &entries)?;
+    transaction.commit()
+}
+
+pub fn move_animal(
+    id: u32,
+    habitat_name: String,
+    reason: String,
+) -> Result<String, Box<dyn Error>> {
+    let Some(mut animal) = reader_animals_alive()?.read_animal_alive_by_id(id)? else {
+        return Ok(format!("There is no animal #{}", id));
+    };
+    if animal.habitat() == habitat_name {
+        return Ok(format!("#{} already lives in {}", id, habitat_name));
+    }
+    let Some(animal_data) = reader_animals_data()?.read_animal_data(&animal.name())? else {
+        return Ok(format!("{} does not exist", animal.name()));
+    };
+    if let Some(problem) = placement_problem(&habitat_name, &animal_data, 1)? {
+        return Ok(problem);
+    }
+    let warning = cohabitation_problem(&habitat_name, &animal_data)?;
+    if let Some(warning) = &warning {
+        if cohabitation::is_strict() {
+            return Ok(format!("{}, not moving it", warning));
+        }
+    }
+    let from = animal.habitat().to_string();
+    animal.habitat = habitat_name.clone();
+    let mut transaction = Transaction::new();
+    stage_animal(
+        &mut transaction,
+        &animal,
+        "move",
+        "moved",
+        &format!("from {} to {}, {}", from, habitat_name, reason),
+    )?;
+    log_transfers(
+        &mut transaction,
+        &[Transfer {
+            id,
+            from: Some(from.clone()),
+            to: habitat_name.clone(),
+            reason,
+            moved_at: clock::now(),
+        }],
+    )?;
+    transaction.commit()?;
+    let message = format!(
+        "{} #{} moved from {} to {}",
+        animal.name(),
+        id,
+        from,
+        habitat_name
+    );
+    match warning {
+        Some(warning) => Ok(format!("{}\nCareful! {}", message, warning)),
+        None => Ok(message),
+    }
+}
+
+pub fn location_history(id: u32) -> Result<String, Box<dyn Error>> {
+    let transfers = reader_transfers()?.read_transfers_of(id)?;
+    if transfers.is_empty() {
+        return Ok(format!("#{} has never been placed anywhere", id));
+    }
+    Ok(transfers
+        .iter()
+        .map(|transfer| transfer.describe())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn birth_message(animal: &AnimalAlive) -> String {
+    format!(
+        "A {} has borned! It's a {} (#{})",
+        animal.name(),
+        animal.sex_str(),
+        animal.id()
+    )
+}
+
+pub fn spawn_animal(
+    animal_name: String,
+    animal_sex: Sex,
+    habitat_name: String,
+) -> Result<String, Box<dyn Error>> {
+    let Some(animal_data) = reader_animals_data()?.read_animal_data(&animal_name)? else {
+        return Ok(format!("{} does not exist", animal_name));
+    };
+    if let Some(problem) = placement_problem(&habitat_name, &animal_data, 1)? {
+        return Ok(problem);
+    }
+    let warning = cohabitation_problem(&habitat_name, &animal_data)?;
+    if let Some(warning) = &warning {
+        if cohabitation::is_strict() {
+            return Ok(format!("{}, not spawning it", warning));
+        }
+    }
+    let message = settle_newcomer(&animal_data, animal_sex, habitat_name, "spawn")?;
+    match warning {
+        Some(warning) => Ok(format!("{}\nCareful! {}", message, warning)),
+        None => Ok(message),
+    }
+}
+
+/// Id for a newborn, past every id in the studbook and among the animals
+/// alive, who can predate the studbook.
+fn next_animal_id() -> Result<u32, Box<dyn Error>> {
+    let studbook_next_id = reader_studbook()?.next_animal_id()?;
+    let alive_next_id = reader_animals_alive()?
+        .read_animals_alive()?
+        .iter()
+        .map(|animal| animal.id() + 1)
+        .max()
+        .unwrap_or(1);
+    Ok(studbook_next_id.max(alive_next_id))
+}
+
+/// Brings an animal with no known parents into a habitat.
+fn settle_newcomer(
+    animal_data: &AnimalData,
+    animal_sex: Sex,
+    habitat_name: String,
+    command: &str,
+) -> Result<String, Box<dyn Error>> {
+    let id = next_animal_id()?;
+    let animal_sex = animal_data.reproduction.spawn_sex(animal_sex);
+    let mut animal = AnimalAlive::born(id, &animal_data.name, animal_sex);
+    animal.genotype = Genotype::random(&animal_data.traits, &mut thread_rng());
+    animal.habitat = habitat_name;
+    let message = birth_message(&animal);
+    give_birth(&[animal], None, None, command)?;
+    Ok(message)
+}
+
+/// Chance of a birth given how crowded the species and the habitat are.
+fn breeding_birth_chance(
+    animal_data: &AnimalData,
+    habitat_name: &str,
+) -> Result<f64, Box<dyn Error>> {
+    let population = reader_animals_alive()?.count_animal(&animal_data.name)? as u32;
+    let habitat_chance = match reader_habitats()?.read_habitat(habitat_name)? {
+        Some(habitat) => {
+            let residents = animals_alive_in(habitat_name)?.len() as u32;
+            logistic_birth_chance(residents, habitat.capacity)
+        }
+        None => 1.0,
+    };
+    Ok(animal_data.birth_chance(population) * habitat_chance)
+}
+
+pub fn capacity_report() -> Result<String, Box<dyn Error>> {
+    let mut report = Vec::new();
+    for animal_data in reader_animals_data()?.read_animals_data()? {
+        let population = reader_animals_alive()?.count_animal(&animal_data.name)? as u32;
+        report.push(match animal_data.carrying_capacity {
+            Some(capacity) => format!(
+                "{}: {}/{}, {:.0}% birth chance",
+                animal_data.name,
+                population,
+                capacity,
+                animal_data.birth_chance(population) * 100.0
+            ),
+            None => format!("{}: {}, no limit", animal_data.name, population),
+        });
+    }
+    for habitat in reader_habitats()?.read_habitats()? {
+        let residents = animals_alive_in(&habitat.name)?.len() as u32;
+        report.push(format!(
+            "{} habitat: {}/{}, {:.0}% birth chance",
+            habitat.name,
+            residents,
+            habitat.capacity,
+            logistic_birth_chance(residents, habitat.capacity) * 100.0
+        ));
+    }
+    if report.is_empty() {
+        Ok("There are no animals nor habitats yet".to_string())
+    } else {
+        Ok(report.join("\n"))
+    }
+}
+
+/// Gives birth to a clutch of offspring of the mother, and of the father
+/// unless the species reproduces asexually, and lets the parents rest.
+fn breed_pair(
+    animal_data: &AnimalData,
+    mut mother: AnimalAlive,
+    father: Option<AnimalAlive>,
+    command: &str,
+) -> Result<String, Box<dyn Error>> {
+    if mother.habitat().is_empty() {
+        return Ok(format!(
+            "#{} lives in no habitat, move it to one before breeding",
+            mother.id()
+        ));
+    }
+    // Offspring stay with their mother, as many as there is room for.
+    if let Some(problem) = placement_problem(mother.habitat(), animal_data, 1)? {
+        return Ok(problem);
+    }
+    let laid = animal_data.clutch_size.max(1);
+    let clutch_size = laid.min(room_left(mother.habitat())?);
+    let mut rng = thread_rng();
+    let birth_chance = breeding_birth_chance(animal_data, mother.habitat())?;
+    if !rng.gen_bool(birth_chance) {
+        return Ok(format!(
+            "Too crowded: the {}s did not breed this time ({:.0}% chance)",
+            animal_data.name,
+            birth_chance * 100.0
+        ));
+    }
+    let now = clock::now();
+    let first_id = next_animal_id()?;
+    let mut offspring = Vec::new();
+    for id in first_id..first_id + clutch_size {
+        let animal_sex = animal_data.offspring_sex(climate::temperature(), &mut rng);
+        let mut animal = AnimalAlive::born(id, &mother.name(), animal_sex);
+        animal.genotype = match &father {
+            Some(father) => Genotype::inherit(mother.genotype(), father.genotype(), &mut rng),
+            None => mother.genotype().clone(),
+        };
+        animal.stage = animal_data.stage_at(0);
+        animal.habitat = mother.habitat().to_string();
+        offspring.push(animal);
+    }
+    let last_id = first_id + clutch_size - 1;
+    let birth = match (offspring[0].stage(), clutch_size) {
+        (LifeStage::Egg, 1) => format!("A {} laid an egg (#{})", mother.name(), first_id),
+        (LifeStage::Egg, _) => format!(
+            "A {} laid {} eggs (#{} to #{})",
+            mother.name(),
+            clutch_size,
+            first_id,
+            last_id
+        ),
+        (_, 1) => birth_message(&offspring[0]),
+        _ => format!(
+            "{} {}s have borned! (#{} to #{})",
+            clutch_size,
+            mother.name(),
+            first_id,
+            last_id
+        ),
+    };
+    give_birth(
+        &offspring,
+        Some(mother.id()),
+        father.as_ref().map(|father| father.id()),
+        command,
+    )?;
+    let birth = if clutch_size < laid {
+        format!(
+            "{}\n{} only had room for {} of the {}",
+            birth,
+            mother.habitat(),
+            clutch_size,
+            laid
+        )
+    } else {
+        birth
+    };
+    mother.bred(now);
+    save_animal(&mother, command, "bred", "mother")?;
+    match father {
+        Some(mut father) => {
+            father.bred(now);
+            save_animal(&father, command, "bred", "father")?;
+            Ok(format!(
+                "{}\nMother: #{}, father: #{}",
+                birth,
+                mother.id(),
+                father.id()
+            ))
+        }
+        None => Ok(format!("{}\nMother: #{}, no father", birth, mother.id())),
+    }
+}
+
+fn out_of_season(animal_data: &AnimalData) -> String {
+    format!(
+        "{} only breeds in {}, it's {} now",
+        animal_data.name,
+        animal_data.breeding_seasons_str(),
+        climate::season().as_str()
+    )
+}
+
+fn missing_parents(animal_data: &AnimalData) -> String {
+    match animal_data.reproduction {
+        ReproductionMode::Asexual => format!("{} has no female to lay eggs", animal_data.name),
+        ReproductionMode::Hermaphrodite => format!("{} needs two hermaphrodites", animal_data.name),
+        ReproductionMode::Sexual
+        | ReproductionMode::Protandrous
+        | ReproductionMode::Protogynous => format!("{} does not have both sexes", animal_data.name),
+    }
+}
+
 pub fn reproduce_animal(animal_name: String) -> Result<String, Box<dyn Error>> {
-    let mut reader = reader_animals_alive()?;
     if !animal_alive(&animal_name)? {
         return Ok(format!("{} is not even alive", animal_name));
-    } else if reader.has_both_sexes(animal_name.as_str())? {
-        let animal_sex = {
-            let mut rng = thread_rng();
-            [Sex::Male, Sex::Female].choose(&mut rng).unwrap().clone()
-        };
-        Ok(spawn_animal(animal_name, animal_sex)?)
-    } else {
-        Ok(format!("{} does not have both sexes", animal_name))
+    }
+    let Some(animal_data) = reader_animals_data()?.read_animal_data(&animal_name)? else {
+        return Ok(format!("{} does not exist", animal_name));
+    };
+    if !animal_data.breeds_in(climate::season()) {
+        return Ok(out_of_season(&animal_data));
+    }
+    let mut animals = animals_alive_of(&animal_name)?;
+    animals.retain(|animal| animal.is_adult());
+    animals.shuffle(&mut thread_rng());
+    let mut parent_of_sex = |sex: Sex| {
+        let index = animals.iter().position(|animal| animal.sex() == &sex)?;
+        Some(animals.swap_remove(index))
+    };
+    let (mother_sex, father_sex) = animal_data.reproduction.parent_sexes();
+    let mother = parent_of_sex(mother_sex);
+    let father = father_sex.map(parent_of_sex);
+    match (mother, father) {
+        (Some(mother), None) => breed_pair(&animal_data, mother, None, "reproduce"),
+        (Some(mother), Some(Some(father))) => {
+            breed_pair(&animal_data, mother, Some(father), "reproduce")
+        }
+        _ => Ok(missing_parents(&animal_data)),
     }
 }
 
+/// Breeds two chosen animals, checking they are a ready to breed couple.
+pub fn breed_animals(mother_id: u32, father_id: u32) -> Result<String, Box<dyn Error>> {
+    if mother_id == father_id {
+        return Ok(format!("#{} can't breed with itself", mother_id));
+    }
+    let mut reader = reader_animals_alive()?;
+    let Some(mother) = reader.read_animal_alive_by_id(mother_id)? else {
+        return Ok(format!("There is no animal #{}", mother_id));
+    };
+    let Some(father) = reader.read_animal_alive_by_id(father_id)? else {
+        return Ok(format!("There is no animal #{}", father_id));
+    };
+    if mother.name() != father.name() {
+        return Ok(format!(
+            "#{} is a {} but #{} is a {}",
+            mother_id,
+            mother.name(),
+            father_id,
+            father.name()
+        ));
+    }
+    let Some(animal_data) = reader_animals_data()?.read_animal_data(&mother.name())? else {
+        return Ok(format!("{} does not exist", mother.name()));
+    };
+    let (mother_sex, Some(father_sex)) = animal_data.reproduction.parent_sexes() else {
+        return Ok(format!(
+            "{} reproduces asexually, try reproduce instead",
+            mother.name()
+        ));
+    };
+    if !animal_data.breeds_in(climate::season()) {
+        return Ok(out_of_season(&animal_data));
+    }
+    if mother.sex() != &mother_sex {
+        return Ok(format!("#{} is not a {}", mother_id, mother_sex.as_str()));
+    }
+    if father.sex() != &father_sex {
+        return Ok(format!("#{} is not a {}", father_id, father_sex.as_str()));
+    }
+    let now = clock::now();
+    for parent in [&mother, &father] {
+        if !parent.is_adult() {
+            return Ok(format!(
+                "#{} is still {} {}",
+                parent.id(),
+                if parent.stage() == LifeStage::Egg {
+                    "an"
+                } else {
+                    "a"
+                },
+                parent.stage().as_str()
+            ));
+        }
+        if !parent.is_mature(&animal_data, now) {
+            return Ok(format!("#{} is too young to breed", parent.id()));
+        }
+        let rest_left = parent.breeding_rest_left(&animal_data, now);
+        if rest_left > 0 {
+            return Ok(format!(
+                "#{} needs to rest {} before breeding again",
+                parent.id(),
+                clock::format_duration(rest_left)
+            ));
+        }
+    }
+    breed_pair(&animal_data, mother, Some(father), "breed")
+}
+
 pub fn kill_animal(animal_name: String) -> Result<String, Box<dyn Error>> {
     let animal_name = animal_name.as_str();
     let mut reader = reader_animals_data()?;
-    if !animal_alive(&animal_name)? {
+    if !animal_alive(animal_name)? {
         Ok(format!("{} is not even alive", animal_name))
     } else if let Some(animal_data) = reader.read_animal_data(animal_name)? {
         let mut rng = thread_rng();
         let predator = animal_data.predators().choose(&mut rng).unwrap();
-        kill_one_animal_alive(animal_name)?;
+        // Cold-blooded predators hunt worse in the cold.
+        if let Some(predator_data) = reader.read_animal_data(predator)? {
+            let activity = predator_data.blood_type().activity(climate::temperature());
+            if !rng.gen_bool(activity) {
+                return Ok(format!(
+                    "A {} was too cold to catch the {}",
+                    predator, animal_name
+                ));
+            }
+        }
+        let prey = reader_animals_alive()?.read_animal_alive(animal_name)?;
+        bury_animals(
+            &prey.into_iter().collect::<Vec<_>>(),
+            "kill",
+            &format!("hunted by a {}", predator),
+        )?;
 
         Ok(format!("{} was killed by a {}", animal_name, predator))
     } else {
@@ -113,10 +947,34 @@ pub fn feed_animal(animal_name: String) -> Result<String, Box<dyn Error>> {
     if !animal_alive(&animal_name)? {
         Ok(format!("{} is not even alive", animal_name))
     } else if let Some(animal_data) = reader.read_animal_data(animal_name.as_str())? {
+        let now = clock::now();
+        let blood_type = animal_data.blood_type();
+        let Some(mut hungriest) = reader_animals_alive()?
+            .read_animals_alive()?
+            .into_iter()
+            .filter(|animal| animal.name() == animal_name && animal.stage() != LifeStage::Egg)
+            .min_by_key(|animal| animal.current_energy(&blood_type, now))
+        else {
+            return Ok(format!("{} eggs have not hatched yet", animal_name));
+        };
         let mut rng = thread_rng();
+        if !rng.gen_bool(blood_type.activity(climate::temperature())) {
+            return Ok(format!(
+                "{} #{} is too cold to eat",
+                animal_name,
+                hungriest.id()
+            ));
+        }
+        hungriest.fed(now);
         let prey = animal_data.preys().choose(&mut rng).unwrap();
+        save_animal(&hungriest, "feed", "fed", &format!("ate a {}", prey))?;
 
-        Ok(format!("Mmm... That {} was delicious", prey))
+        Ok(format!(
+            "Mmm... That {} was delicious, said {} #{}",
+            prey,
+            animal_name,
+            hungriest.id()
+        ))
     } else {
         Ok("Animal not found".to_string())
     }
@@ -127,15 +985,1046 @@ pub fn count_animal(animal_name: String) -> Result<String, Box<dyn Error>> {
     Ok(format!("We have {} {}s!", count, animal_name))
 }
 
+pub fn count_animal_at(animal_name: String, time: String) -> Result<String, Box<dyn Error>> {
+    let projection = projection_at(&time)?;
+    let count = projection
+        .animals
+        .values()
+        .filter(|row| row[0] == animal_name)
+        .count();
+    Ok(format!("We had {} {}s at {}!", count, animal_name, time))
+}
+
+fn animals_data_by_name() -> Result<HashMap<String, AnimalData>, Box<dyn Error>> {
+    Ok(reader_animals_data()?
+        .read_animals_data()?
+        .into_iter()
+        .map(|animal_data| (animal_data.name(), animal_data))
+        .collect())
+}
+
+pub fn hungry_animals() -> Result<String, Box<dyn Error>> {
+    let now = clock::now();
+    let animals_data = animals_data_by_name()?;
+    let hungry = reader_animals_alive()?
+        .read_animals_alive()?
+        .into_iter()
+        .filter(|animal| animal.stage() != LifeStage::Egg)
+        .filter_map(|animal| {
+            let blood_type = animals_data.get(&animal.name())?.blood_type();
+            animal.is_hungry(&blood_type, now).then(|| {
+                format!(
+                    "{} #{} ({}): energy {}",
+                    animal.name(),
+                    animal.id(),
+                    animal.sex_str(),
+                    animal.current_energy(&blood_type, now)
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+    if hungry.is_empty() {
+        Ok("Nobody is hungry".to_string())
+    } else {
+        Ok(hungry.join("\n"))
+    }
+}
+
+/// Lets the animals live through the time passed since the last command,
+/// returning what happened to them.
+pub fn pass_time() -> Result<Vec<String>, Box<dyn Error>> {
+    let mut events = grow_up_animals()?;
+    events.extend(starve_animals("pass-time")?);
+    events.extend(change_sexes()?);
+    events.extend(spread_disease()?);
+    Ok(events)
+}
+
+/// Lives every whole day passed since the outbreak was last looked at.
+fn spread_disease() -> Result<Vec<String>, Box<dyn Error>> {
+    let mut outbreak = disease::outbreak();
+    let now = clock::now();
+    let mut animals = reader_animals_alive()?.read_animals_alive()?;
+    if !animals
+        .iter()
+        .any(|animal| animal.health() == Health::Infected)
+    {
+        return Ok(Vec::new());
+    }
+    let animals_data = animals_data_by_name()?;
+    let mut rng = thread_rng();
+    let mut events = Vec::new();
+    let mut dead = Vec::new();
+    let mut changed = Vec::new();
+    while outbreak.stepped_at + clock::SECONDS_PER_DAY <= now {
+        outbreak.stepped_at += clock::SECONDS_PER_DAY;
+        let day = disease::spread_for_a_day(
+            &mut animals,
+            &animals_data,
+            outbreak.across_species,
+            &mut rng,
+        );
+        let label = |id: &u32| {
+            let animal = animals.iter().find(|animal| animal.id() == *id).unwrap();
+            format!("{} #{}", animal.name(), id)
+        };
+        for id in &day.infected {
+            events.push(format!("{} caught the disease", label(id)));
+        }
+        for id in &day.recovered {
+            events.push(format!("{} recovered and is now immune", label(id)));
+        }
+        for id in &day.died {
+            events.push(format!("{} died of the disease", label(id)));
+        }
+        outbreak.cases += day.infected.len() as u32;
+        outbreak.deaths += day.died.len() as u32;
+        changed.extend(day.infected);
+        changed.extend(day.recovered);
+        animals.retain(|animal| {
+            if day.died.contains(&animal.id()) {
+                dead.push(animal.clone());
+                false
+            } else {
+                true
+            }
+        });
+    }
+    for animal in &animals {
+        if changed.contains(&animal.id()) {
+            let outcome = match animal.health() {
+                Health::Recovered => "recovered",
+                _ => "infected",
+            };
+            save_animal(animal, "pass-time", outcome, "disease")?;
+        }
+    }
+    bury_animals(&dead, "pass-time", "disease")?;
+    disease::set_outbreak(&outbreak)?;
+    Ok(events)
+}
+
+pub fn infect(animal_name: String, across_species: bool) -> Result<String, Box<dyn Error>> {
+    let animals = animals_alive_of(&animal_name)?;
+    if animals.is_empty() {
+        return Ok(format!("{} is not even alive", animal_name));
+    }
+    let Some(mut patient_zero) = animals
+        .into_iter()
+        .filter(|animal| animal.health() == Health::Susceptible && animal.stage() != LifeStage::Egg)
+        .collect::<Vec<_>>()
+        .choose(&mut thread_rng())
+        .cloned()
+    else {
+        return Ok(format!("No {} can catch the disease", animal_name));
+    };
+    patient_zero.health = Health::Infected;
+    save_animal(&patient_zero, "infect", "infected", "patient zero")?;
+    let mut outbreak = disease::outbreak();
+    let now = clock::now();
+    // A new outbreak starts counting days now.
+    if outbreak.stepped_at + clock::SECONDS_PER_DAY <= now {
+        outbreak.stepped_at = now;
+    }
+    outbreak.across_species = across_species;
+    outbreak.cases += 1;
+    disease::set_outbreak(&outbreak)?;
+    Ok(format!(
+        "{} #{} caught the disease{}",
+        animal_name,
+        patient_zero.id(),
+        if across_species {
+            ", which can spread to its predators and preys"
+        } else {
+            ""
+        }
+    ))
+}
+
+pub fn outbreak_report() -> Result<String, Box<dyn Error>> {
+    let mut counts: BTreeMap<String, [u32; 3]> = BTreeMap::new();
+    for animal in reader_animals_alive()?.read_animals_alive()? {
+        let count = counts.entry(animal.name()).or_default();
+        match animal.health() {
+            Health::Susceptible => count[0] += 1,
+            Health::Infected => count[1] += 1,
+            Health::Recovered => count[2] += 1,
+        }
+    }
+    let outbreak = disease::outbreak();
+    let mut report = vec![format!(
+        "{} cases and {} deaths so far",
+        outbreak.cases, outbreak.deaths
+    )];
+    for (animal_name, [susceptible, infected, recovered]) in counts {
+        report.push(format!(
+            "{}: {} susceptible, {} infected, {} recovered",
+            animal_name, susceptible, infected, recovered
+        ));
+    }
+    Ok(report.join("\n"))
+}
+
+fn grow_up_animals() -> Result<Vec<String>, Box<dyn Error>> {
+    let now = clock::now();
+    let animals_data = animals_data_by_name()?;
+    let mut events = Vec::new();
+    for mut animal in reader_animals_alive()?.read_animals_alive()? {
+        let Some(animal_data) = animals_data.get(&animal.name()) else {
+            continue;
+        };
+        let Some(previous_stage) = animal.grow_up(animal_data, now) else {
+            continue;
+        };
+        save_animal(
+            &animal,
+            "pass-time",
+            "grew-up",
+            &format!("now {}", animal.stage().as_str()),
+        )?;
+        let event = match (previous_stage, animal.stage()) {
+            (LifeStage::Egg, _) => "hatched",
+            (LifeStage::Larva, _) => "metamorphosed into an adult",
+            _ => "grew up",
+        };
+        events.push(format!("{} #{} {}", animal.name(), animal.id(), event));
+    }
+    Ok(events)
+}
+
+fn change_sexes() -> Result<Vec<String>, Box<dyn Error>> {
+    let now = clock::now();
+    let animals_data = animals_data_by_name()?;
+    let mut changes = Vec::new();
+    for mut animal in reader_animals_alive()?.read_animals_alive()? {
+        let Some(animal_data) = animals_data.get(&animal.name()) else {
+            continue;
+        };
+        if animal.change_sex_if_due(animal_data, now) {
+            save_animal(
+                &animal,
+                "pass-time",
+                "changed-sex",
+                &format!("now {}", animal.sex_str()),
+            )?;
+            changes.push(format!(
+                "{} #{} turned {}",
+                animal.name(),
+                animal.id(),
+                animal.sex_str()
+            ));
+        }
+    }
+    Ok(changes)
+}
+
+/// Removes every animal whose energy ran out, returning an obituary per death.
+pub fn starve_animals(command: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let now = clock::now();
+    let animals_data = animals_data_by_name()?;
+    let starved = reader_animals_alive()?
+        .read_animals_alive()?
+        .into_iter()
+        .filter(|animal| {
+            // Eggs live off their yolk.
+            animal.stage() != LifeStage::Egg
+                && animals_data
+                    .get(&animal.name())
+                    .is_some_and(|animal_data| animal.is_starved(&animal_data.blood_type(), now))
+        })
+        .collect::<Vec<_>>();
+    bury_animals(&starved, command, "starvation")?;
+    Ok(starved
+        .iter()
+        .map(|animal| format!("{} #{} starved to death", animal.name(), animal.id()))
+        .collect())
+}
+
+pub fn inspect_animal(id: u32) -> Result<String, Box<dyn Error>> {
+    let Some(animal) = reader_animals_alive()?.read_animal_alive_by_id(id)? else {
+        return Ok(format!("There is no animal #{}", id));
+    };
+    let now = clock::now();
+    let (energy, traits) = match reader_animals_data()?.read_animal_data(&animal.name())? {
+        Some(animal_data) => (
+            animal
+                .current_energy(&animal_data.blood_type(), now)
+                .to_string(),
+            animal.genotype().describe(&animal_data.traits),
+        ),
+        None => ("unknown".to_string(), "unknown".to_string()),
+    };
+    Ok(format!(
+        "Id: {}\nName: {}\nSex: {}\nStage: {}\nHabitat: {}\nHealth: {}\nAge: {}\nEnergy: {}\nTraits: {}",
+        animal.id(),
+        animal.name(),
+        animal.sex_str(),
+        animal.stage().as_str(),
+        animal.habitat(),
+        animal.health().as_str(),
+        clock::format_duration(animal.age(now)),
+        energy,
+        traits
+    ))
+}
+
+pub fn advance_time(seconds: u64) -> Result<String, Box<dyn Error>> {
+    let before = clock::now();
+    let Some(after) = before.checked_add(seconds) else {
+        return Ok("The ecosystem clock can't go that far".to_string());
+    };
+    clock::advance(seconds)?;
+    journal(
+        "advance-time",
+        "",
+        None,
+        "time-advanced",
+        &clock::format_duration(seconds),
+    )?;
+
+    let animals_data = animals_data_by_name()?;
+    let mut rng = thread_rng();
+    let dead = reader_animals_alive()?
+        .read_animals_alive()?
+        .into_iter()
+        .filter(|animal| {
+            animals_data.get(&animal.name()).is_some_and(|animal_data| {
+                let probability =
+                    animal_data.old_age_death_probability(animal.age(before), animal.age(after));
+                rng.gen_bool(probability)
+            })
+        })
+        .collect::<Vec<_>>();
+    bury_animals(&dead, "advance-time", "old age")?;
+
+    let mut deaths = BTreeMap::new();
+    for animal in &dead {
+        *deaths.entry((animal.name(), animal.sex_str())).or_insert(0) += 1;
+    }
+    let mut report = vec![format!("{} went by", clock::format_duration(seconds))];
+    if deaths.is_empty() {
+        report.push("Nobody died of old age".to_string());
+    }
+    for ((animal_name, animal_sex), count) in deaths {
+        report.push(format!(
+            "{} {} {}(s) died of old age",
+            count, animal_sex, animal_name
+        ));
+    }
+    report.extend(pass_time()?);
+    Ok(report.join("\n"))
+}
+
+pub fn pedigree(id: u32) -> Result<String, Box<dyn Error>> {
+    let pedigree = Pedigree::new(reader_studbook()?.read_studbook()?);
+    match (pedigree.ancestors_tree(id), pedigree.descendants_tree(id)) {
+        (Some(ancestors), Some(descendants)) => Ok(format!(
+            "Ancestors:\n{}\n\nDescendants:\n{}",
+            ancestors, descendants
+        )),
+        _ => Ok(format!("There is no animal #{} in the studbook", id)),
+    }
+}
+
+pub fn export_animal_studbook(animal_name: String) -> Result<String, Box<dyn Error>> {
+    let path = format!("{}_studbook.csv", animal_name);
+    let count = export_studbook(&animal_name, &path)?;
+    Ok(format!("{} {}s exported to {}", count, animal_name, path))
+}
+
+pub fn kinship(animal_name: String) -> Result<String, Box<dyn Error>> {
+    let animals = animals_alive_of(&animal_name)?;
+    if animals.is_empty() {
+        return Ok(format!("{} is not even alive", animal_name));
+    }
+    let pedigree = Pedigree::new(reader_studbook()?.read_studbook()?);
+    let population = animals.iter().map(|animal| animal.id()).collect::<Vec<_>>();
+    Ok(animals
+        .iter()
+        .map(|animal| {
+            format!(
+                "{} #{} ({}): inbreeding {:.3}, mean kinship {:.3}",
+                animal.name(),
+                animal.id(),
+                animal.sex_str(),
+                pedigree.inbreeding(animal.id()),
+                pedigree.mean_kinship(animal.id(), &population)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Pairs up mothers and fathers so that offspring are as little inbred as
+/// possible, preferring the least related animals of the population on ties.
+pub fn recommend_pairs(animal_name: String) -> Result<String, Box<dyn Error>> {
+    let Some(animal_data) = reader_animals_data()?.read_animal_data(&animal_name)? else {
+        return Ok(format!("{} does not exist", animal_name));
+    };
+    let (mother_sex, Some(father_sex)) = animal_data.reproduction.parent_sexes() else {
+        return Ok(format!("{} reproduces asexually", animal_name));
+    };
+    let animals = animals_alive_of(&animal_name)?;
+    let pedigree = Pedigree::new(reader_studbook()?.read_studbook()?);
+    let population = animals.iter().map(|animal| animal.id()).collect::<Vec<_>>();
+    let of_sex = |sex: Sex| {
+        animals
+            .iter()
+            .filter(|animal| animal.sex() == &sex && animal.is_adult())
+            .map(|animal| animal.id())
+            .collect::<Vec<_>>()
+    };
+
+    let mut candidates = Vec::new();
+    for mother in of_sex(mother_sex) {
+        for father in of_sex(father_sex) {
+            // Hermaphrodites could be paired both ways round, keep one.
+            if mother_sex == father_sex && mother >= father {
+                continue;
+            }
+            let offspring_inbreeding = pedigree.kinship(mother, father);
+            let mean_kinship = pedigree.mean_kinship(mother, &population)
+                + pedigree.mean_kinship(father, &population);
+            candidates.push((offspring_inbreeding, mean_kinship, mother, father));
+        }
+    }
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+
+    let mut paired = Vec::new();
+    let mut pairs = Vec::new();
+    for (offspring_inbreeding, _, mother, father) in candidates {
+        if paired.contains(&mother) || paired.contains(&father) {
+            continue;
+        }
+        paired.extend([mother, father]);
+        pairs.push(format!(
+            "{} #{} x {} #{}: offspring inbreeding {:.3}",
+            mother_sex.as_str(),
+            mother,
+            father_sex.as_str(),
+            father,
+            offspring_inbreeding
+        ));
+    }
+    if pairs.is_empty() {
+        Ok(missing_parents(&animal_data))
+    } else {
+        Ok(pairs.join("\n"))
+    }
+}
+
+pub fn allele_report(animal_name: String) -> Result<String, Box<dyn Error>> {
+    let Some(animal_data) = reader_animals_data()?.read_animal_data(&animal_name)? else {
+        return Ok(format!("{} does not exist", animal_name));
+    };
+    if animal_data.traits.is_empty() {
+        return Ok(format!("{} has no heritable traits", animal_name));
+    }
+    let animals = animals_alive_of(&animal_name)?;
+    let genotypes = animals
+        .iter()
+        .map(|animal| animal.genotype())
+        .collect::<Vec<_>>();
+    let mut report = vec![format!("{} {}s alive", animals.len(), animal_name)];
+    for heritable_trait in &animal_data.traits {
+        let frequencies = allele_frequencies(heritable_trait, &genotypes)
+            .into_iter()
+            .map(|(allele, frequency)| format!("{} {:.1}%", allele, frequency * 100.0))
+            .collect::<Vec<_>>();
+        report.push(format!(
+            "{}: {}",
+            heritable_trait.name,
+            frequencies.join(", ")
+        ));
+    }
+    Ok(report.join("\n"))
+}
+
+pub fn season() -> Result<String, Box<dyn Error>> {
+    let temperature = climate::temperature();
+    let season = climate::season();
+    let mut report = vec![format!(
+        "It's {}, {} is here and it's {:.1}°C",
+        clock::format_date(clock::now()),
+        season.as_str(),
+        temperature
+    )];
+    for animal_data in reader_animals_data()?.read_animals_data()? {
+        report.push(format!(
+            "{}: {}, activity {:.0}%",
+            animal_data.name,
+            if animal_data.breeds_in(season) {
+                "breeding season".to_string()
+            } else {
+                format!("breeds in {}", animal_data.breeding_seasons_str())
+            },
+            animal_data.blood_type().activity(temperature) * 100.0
+        ));
+    }
+    Ok(report.join("\n"))
+}
+
+pub fn set_climate(input: &str) -> Result<String, Box<dyn Error>> {
+    let Some(climate) = climate::Climate::from_text(input) else {
+        return Ok("Invalid climate".to_string());
+    };
+    climate::set_climate(&climate)?;
+    journal("set-climate", "", None, "climate-set", input)?;
+    Ok(format!(
+        "Temperatures now swing {}°C around {}°C, it's {:.1}°C today",
+        climate.seasonal_amplitude,
+        climate.mean_temperature,
+        climate::temperature()
+    ))
+}
+
+/// Predicted share of each sex among newborns around the pivotal temperature.
+pub fn sex_ratios(animal_name: String) -> Result<String, Box<dyn Error>> {
+    let Some(animal_data) = reader_animals_data()?.read_animal_data(&animal_name)? else {
+        return Ok(format!("{} does not exist", animal_name));
+    };
+    let Some(pivotal_temperature) = animal_data.pivotal_temperature else {
+        return Ok(format!(
+            "{} sex does not depend on temperature",
+            animal_name
+        ));
+    };
+    let current = climate::temperature();
+    let mut report = vec![format!(
+        "{} pivotal temperature: {}°C, currently {}°C",
+        animal_name, pivotal_temperature.celsius, current
+    )];
+    let coldest =
+        (pivotal_temperature.celsius - pivotal_temperature.transitional_range).floor() as i32;
+    let warmest =
+        (pivotal_temperature.celsius + pivotal_temperature.transitional_range).ceil() as i32;
+    for celsius in coldest..=warmest {
+        let warm_sex_ratio = pivotal_temperature.warm_sex_ratio(celsius as f64);
+        report.push(format!(
+            "{}°C: {:.0}% {}, {:.0}% {}",
+            celsius,
+            warm_sex_ratio * 100.0,
+            pivotal_temperature.warm_sex.as_str(),
+            (1.0 - warm_sex_ratio) * 100.0,
+            pivotal_temperature.cold_sex().as_str()
+        ));
+    }
+    Ok(report.join("\n"))
+}
+
+/// Rolls an event from the environmental events table and lets it fall on
+/// the animals it targets.
+pub fn roll_environmental_event() -> Result<String, Box<dyn Error>> {
+    let table = reader_environmental_events()?.read_environmental_events()?;
+    let mut rng = thread_rng();
+    let Some(event) = roll_event(&table, &mut rng) else {
+        return Ok("The environmental events table is empty".to_string());
+    };
+    let now = clock::now();
+    let animals_data = animals_data_by_name()?;
+    let all_habitats = reader_habitats()?.read_habitats()?;
+    let biomes = all_habitats
+        .iter()
+        .map(|habitat| (habitat.name.as_str(), habitat.biome.as_str()))
+        .collect::<HashMap<_, _>>();
+    let targeted = reader_animals_alive()?
+        .read_animals_alive()?
+        .into_iter()
+        .filter_map(|animal| {
+            let animal_data = animals_data.get(&animal.name())?;
+            let biome = biomes.get(animal.habitat()).copied().unwrap_or("");
+            event
+                .target
+                .matches(&animal, animal_data, biome)
+                .then_some((animal, animal_data))
+        })
+        .collect::<Vec<_>>();
+
+    let mut aftermath = Vec::new();
+    let (affected, summary) = match &event.effect {
+        EventEffect::Nothing => (0, "nothing happened".to_string()),
+        EventEffect::Kill(chance) => {
+            let dead = targeted
+                .into_iter()
+                .filter(|_| rng.gen_bool(*chance))
+                .map(|(animal, _)| animal)
+                .collect::<Vec<_>>();
+            bury_animals(&dead, "event", &event.name)?;
+            let mut deaths = BTreeMap::new();
+            for animal in &dead {
+                *deaths.entry(animal.name()).or_insert(0) += 1;
+            }
+            let deaths = deaths
+                .into_iter()
+                .map(|(animal_name, count)| format!("{} {}", count, animal_name))
+                .collect::<Vec<_>>();
+            let summary = if deaths.is_empty() {
+                "nobody died".to_string()
+            } else {
+                format!("killed {}", deaths.join(", "))
+            };
+            (dead.len() as u32, summary)
+        }
+        EventEffect::Starve(energy) => {
+            for (animal, animal_data) in &targeted {
+                let mut animal = animal.clone();
+                animal.lose_energy(*energy, &animal_data.blood_type(), now);
+                save_animal(&animal, "event", "weakened", &event.name)?;
+            }
+            aftermath = starve_animals("event")?;
+            (
+                targeted.len() as u32,
+                format!("{} animals lost {} energy", targeted.len(), energy),
+            )
+        }
+        EventEffect::Migrate(count) => {
+            let EventTarget::Species(animal_name) = &event.target else {
+                return Ok(format!(
+                    "{} is a migration but does not target a species",
+                    event.name
+                ));
+            };
+            let Some(animal_data) = animals_data.get(animal_name) else {
+                return Ok(format!("{} does not exist", animal_name));
+            };
+            let mut arrived = 0;
+            for _ in 0..*count {
+                // Migrants join their kind, or settle wherever they fit.
+                let mut habitats = animals_alive_of(animal_name)?
+                    .iter()
+                    .map(|animal| animal.habitat().to_string())
+                    .collect::<Vec<_>>();
+                habitats.extend(all_habitats.iter().map(|habitat| habitat.name.clone()));
+                let mut settled = false;
+                for habitat_name in habitats {
+                    if placement_problem(&habitat_name, animal_data, 1)?.is_none()
+                        && (!cohabitation::is_strict()
+                            || cohabitation_problem(&habitat_name, animal_data)?.is_none())
+                    {
+                        let animal_sex = *[Sex::Male, Sex::Female].choose(&mut rng).unwrap();
+                        aftermath.push(settle_newcomer(
+                            animal_data,
+                            animal_sex,
+                            habitat_name,
+                            "event",
+                        )?);
+                        settled = true;
+                        break;
+                    }
+                }
+                if !settled {
+                    break;
+                }
+                arrived += 1;
+            }
+            (
+                arrived,
+                format!("{} of {} {}s moved in", arrived, count, animal_name),
+            )
+        }
+    };
+    journal(
+        "event",
+        "",
+        None,
+        "event-rolled",
+        &format!("{}: {}", event.name, summary),
+    )?;
+    writer_environmental_events_log()?.write_applied_event(&AppliedEvent {
+        name: event.name.clone(),
+        applied_at: now,
+        affected,
+        summary: summary.clone(),
+    })?;
+    let mut report = vec![format!("{}: {}", event.name, summary)];
+    report.extend(aftermath);
+    Ok(report.join("\n"))
+}
+
+/// Journal entries matching a species, an outcome and a time range, `all`
+/// matching anything. Ranges are dates like `2026-03-01..2026-04-01`, and
+/// either end can be left open.
+pub fn history(species: String, outcome: String, range: String) -> Result<String, Box<dyn Error>> {
+    let any = |filter: String| (filter != "all").then_some(filter);
+    // Either end of `from..to` can be left out, but not mistyped.
+    let end = |date: &str| match date {
+        "" => Some(None),
+        date => clock::parse_date(date).map(Some),
+    };
+    let (from, to) = if range == "all" {
+        (None, None)
+    } else {
+        let Some((Some(from), Some(to))) = range
+            .split_once("..")
+            .map(|(from, to)| (end(from), end(to)))
+        else {
+            return Ok(format!("Invalid dates {}, use from..to or all", range));
+        };
+        (from, to.map(|to| to + clock::SECONDS_PER_DAY))
+    };
+    let query = JournalQuery {
+        operation: None,
+        species: any(species),
+        outcome: any(outcome),
+        from,
+        to,
+    };
+    let entries = reader_journal()?.read_journal(&query)?;
+    if entries.is_empty() {
+        return Ok("Nothing happened matching that".to_string());
+    }
+    Ok(entries
+        .iter()
+        .map(|entry| entry.describe())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Row changes an operation made, merged per species or animal into the
+/// first row before and the last row after. Changes made by time passing
+/// before the command are left out, as they can't be undone.
+fn operation_changes(operation: u64) -> Result<Vec<JournalEntry>, Box<dyn Error>> {
+    let query = JournalQuery {
+        operation: Some(operation),
+        ..Default::default()
+    };
+    let mut changes: Vec<JournalEntry> = Vec::new();
+    for entry in reader_journal()?.read_journal(&query)? {
+        if !undoable(&entry) {
+            continue;
+        }
+        match changes
+            .iter_mut()
+            .find(|change| change.species == entry.species && change.individual == entry.individual)
+        {
+            Some(change) => change.after = entry.after,
+            None => changes.push(entry),
+        }
+    }
+    Ok(changes)
+}
+
+/// Whether an entry changed a row the operation can take back, time passing
+/// before the command not being part of it.
+fn undoable(entry: &JournalEntry) -> bool {
+    entry.changes_row() && entry.command != "pass-time"
+}
+
+/// Command that made the first of an operation's changes.
+fn changes_command(changes: &[JournalEntry]) -> String {
+    changes
+        .first()
+        .map(|change| change.command.clone())
+        .unwrap_or_default()
+}
+
+/// Current row of the species or animal a journal entry changed.
+fn current_row(entry: &JournalEntry) -> Result<Option<Vec<String>>, Box<dyn Error>> {
+    Ok(match entry.individual {
+        Some(id) => reader_animals_alive()?
+            .read_animal_alive_by_id(id)?
+            .map(|animal| animal.to_csv().to_vec()),
+        None => reader_animals_data()?
+            .read_animal_data(&entry.species)?
+            .map(|animal_data| animal_data.to_csv().to_vec()),
+    })
+}
+
+/// Stages putting back the row of the species or animal a journal entry
+/// changed, keeping the studbook in line with who is alive.
+fn restore_row(
+    transaction: &mut Transaction,
+    entry: &JournalEntry,
+    row: &Option<Vec<String>>,
+) -> Result<(), Box<dyn Error>> {
+    let record = row.as_ref().map(|row| StringRecord::from(row.clone()));
+    match (entry.individual, record) {
+        (Some(id), None) => {
+            kill_animals_alive_by_id(transaction, &[id])?;
+            record_deaths(transaction, &[id], clock::now())?;
+        }
+        (Some(id), Some(record)) => {
+            let animal = AnimalAlive::from_csv(&record)?;
+            if update_animal_alive(transaction, &animal)?.is_none() {
+                revive_animal_alive(transaction, &animal)?;
+                record_revivals(transaction, &[id])?;
+            }
+        }
+        (None, record) => {
+            delete_animal_data(transaction, &entry.species)?;
+            if let Some(record) = record {
+                append_animal_data(transaction, &AnimalData::from_csv(&record)?)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reverses an operation's row changes, or makes them again when redoing,
+/// unless a row changed since. Returns what changed since, if anything did.
+/// Every row and its journal entry change together or not at all.
+fn replay_operation(
+    changes: &[JournalEntry],
+    redo: bool,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let (command, outcome) = if redo {
+        ("redo", "redone")
+    } else {
+        ("undo", "undone")
+    };
+    for change in changes {
+        let expected = if redo { &change.before } else { &change.after };
+        if &current_row(change)? != expected {
+            let subject = match change.individual {
+                Some(id) => format!("{} #{}", change.species, id),
+                None => change.species.clone(),
+            };
+            return Ok(Some(format!("{} changed since", subject)));
+        }
+    }
+    let mut transaction = Transaction::new();
+    let mut entries = Vec::new();
+    for change in changes {
+        let (from, to) = if redo {
+            (change.before.clone(), change.after.clone())
+        } else {
+            (change.after.clone(), change.before.clone())
+        };
+        restore_row(&mut transaction, change, &to)?;
+        entries.push(journal_entry(
+            command,
+            &change.species,
+            change.individual,
+            outcome,
+            &change.outcome,
+            from,
+            to,
+        ));
+    }
+    append_journal_entries(&mut transaction, &entries)?;
+    transaction.commit()?;
+    Ok(None)
+}
+
+/// Makes the operation that just ran undoable if it changed a species or
+/// an animal.
+pub fn end_operation() -> Result<(), Box<dyn Error>> {
+    let mut history = undo::history();
+    let changes = undo::operation_entries()?
+        .into_iter()
+        .filter(undoable)
+        .collect::<Vec<_>>();
+    let command = changes_command(&changes);
+    if !command.is_empty() && command != "undo" && command != "redo" {
+        history.done(history.operation);
+        undo::set_history(&history)?;
+    }
+    Ok(())
+}
+
+/// Reverses the last `count` operations, restoring species and animals
+/// exactly as they were.
+pub fn undo(count: usize) -> Result<String, Box<dyn Error>> {
+    let mut report = Vec::new();
+    for _ in 0..count {
+        let mut history = undo::history();
+        let Some(&operation) = history.undoable.last() else {
+            report.push("Nothing left to undo".to_string());
+            break;
+        };
+        let changes = operation_changes(operation)?;
+        let command = changes_command(&changes);
+        if let Some(problem) = replay_operation(&changes, false)? {
+            report.push(format!("Can't undo {}: {}", command, problem));
+            break;
+        }
+        history.undone(operation);
+        undo::set_history(&history)?;
+        report.push(format!("Undid {}", command));
+    }
+    Ok(report.join("\n"))
+}
+
+/// Makes again the last `count` operations undone.
+pub fn redo(count: usize) -> Result<String, Box<dyn Error>> {
+    let mut report = Vec::new();
+    for _ in 0..count {
+        let mut history = undo::history();
+        let Some(&operation) = history.redoable.last() else {
+            report.push("Nothing left to redo".to_string());
+            break;
+        };
+        let changes = operation_changes(operation)?;
+        let command = changes_command(&changes);
+        if let Some(problem) = replay_operation(&changes, true)? {
+            report.push(format!("Can't redo {}: {}", command, problem));
+            break;
+        }
+        history.redone(operation);
+        undo::set_history(&history)?;
+        report.push(format!("Redid {}", command));
+    }
+    Ok(report.join("\n"))
+}
+
+/// Rebuilds the species and animals alive from the journal, up to the end
+/// of a `YYYY-MM-DD` date or `all` of it, writing them next to the real
+/// files. A full replay is checked against the real files.
+pub fn replay(until: String) -> Result<String, Box<dyn Error>> {
+    let to = match clock::parse_date(&until) {
+        _ if until == "all" => None,
+        Some(date) => Some(date + clock::SECONDS_PER_DAY),
+        None => {
+            return Ok(format!(
+                "Invalid date {}, use a date like 2026-03-01 or all",
+                until
+            ))
+        }
+    };
+    let query = JournalQuery {
+        to,
+        ..Default::default()
+    };
+    let projection = Projection::from_journal(&reader_journal()?.read_journal(&query)?);
+    write_rows(
+        REPLAYED_ANIMALS_DATA_FILE_PATH,
+        &ANIMALS_DATA_HEADERS,
+        projection.species.values(),
+    )?;
+    write_rows(
+        REPLAYED_ANIMALS_ALIVE_FILE_PATH,
+        &ANIMALS_ALIVE_HEADERS,
+        projection.animals.values(),
+    )?;
+    let mut report = vec![format!(
+        "Replayed {} species and {} animals alive into {} and {}",
+        projection.species.len(),
+        projection.animals.len(),
+        REPLAYED_ANIMALS_DATA_FILE_PATH,
+        REPLAYED_ANIMALS_ALIVE_FILE_PATH
+    )];
+    if query.to.is_some() {
+        return Ok(report.join("\n"));
+    }
+    let species_rows = reader_animals_data()?
+        .read_animals_data()?
+        .iter()
+        .map(|animal_data| animal_data.to_csv().to_vec())
+        .collect::<Vec<_>>();
+    let animal_rows = reader_animals_alive()?
+        .read_animals_alive()?
+        .iter()
+        .map(|animal| animal.to_csv().to_vec())
+        .collect::<Vec<_>>();
+    let divergences = projection.divergences(&species_rows, &animal_rows);
+    if divergences.is_empty() {
+        report.push("The files match the journal".to_string());
+    } else {
+        report.push(format!("{} divergences:", divergences.len()));
+        report.extend(divergences);
+    }
+    Ok(report.join("\n"))
+}
+
+fn write_rows<'a>(
+    path: &str,
+    headers: &[&str],
+    rows: impl Iterator<Item = &'a Vec<String>>,
+) -> Result<(), Box<dyn Error>> {
+    let mut writer = create_writer_truncate_for_path(path)?;
+    writer.inner.write_record(headers)?;
+    for row in rows {
+        writer.inner.write_record(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn environmental_events_log() -> Result<String, Box<dyn Error>> {
+    let events = reader_environmental_events_log()?.read_applied_events()?;
+    if events.is_empty() {
+        return Ok("No environmental event happened yet".to_string());
+    }
+    Ok(events
+        .iter()
+        .map(|event| {
+            format!(
+                "{}: {}, {}",
+                clock::format_date(event.applied_at),
+                event.name,
+                event.summary
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+pub fn environmental_events_table() -> Result<String, Box<dyn Error>> {
+    let table = reader_environmental_events()?.read_environmental_events()?;
+    let total_weight = table.iter().map(|event| event.weight).sum::<u32>().max(1);
+    Ok(table
+        .iter()
+        .map(|event| {
+            format!(
+                "{}: {:.0}% chance, {} on {}",
+                event.name,
+                event.weight as f64 / total_weight as f64 * 100.0,
+                event_effect_to_csv(&event.effect),
+                event_target_to_csv(&event.target)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Grid simulation seeded with the animals alive, leaving the files untouched.
+pub fn simulation_grid(width: usize, height: usize) -> Result<Grid, Box<dyn Error>> {
+    Ok(Grid::populate(
+        width,
+        height,
+        reader_animals_alive()?.read_animals_alive()?,
+        animals_data_by_name()?,
+        &mut thread_rng(),
+    ))
+}
+
 pub fn help() -> String {
-    "create 'animal_name': Create an animal, specifying class, preys and predators
+    "create 'animal_name': Create an animal, specifying class, preys, predators, lifespan, maturity, rest between breedings, life stages, breeding seasons, carrying capacity, heritable traits and reproduction mode
 delete 'animal_name': Delete an animal data
-read 'animal_name': Read animal data
-spawn 'animal_name': Spawn animal of chosen sex
-reproduce 'animal_name': Reproduce an animal alive
+read '[--at time]' 'animal_name': Read animal data, or as it was at a time
+spawn 'animal_name': Spawn animal of chosen sex in a habitat
+reproduce 'animal_name': Reproduce adults alive, sexually or not depending on their reproduction mode, giving birth or laying a clutch of eggs
+breed 'mother_id' 'father_id': Breed two mature and rested animals alive in their breeding season
 kill 'animal_name': Kill an animal alive
-feed 'animal_name': Feed an animal alive
-count 'animal_name': Count the number of animals alive
+feed 'animal_name': Feed an animal alive, if it's warm enough for cold-blooded ones
+count '[--at time]' 'animal_name': Count the number of animals alive, or alive at a time
+hungry: List the animals alive that need feeding
+inspect 'id': Show life stage, habitat, health, age and energy of an animal alive
+advance-time 'duration': Age everyone by a duration like 12h, 3d, 2w or 1y
+pedigree 'id': Show the ancestors and descendants of an animal
+export-studbook 'animal_name': Export every animal ever born of a kind to a studbook CSV
+kinship 'animal_name': Show inbreeding and mean kinship of the animals alive
+recommend-pairs 'animal_name': Propose breeding pairs with the least inbred offspring
+alleles 'animal_name': Show allele frequencies of the heritable traits among animals alive
+season: Show the date, season and temperature, and which animals breed and how active they are
+set-climate 'mean:swing': Set the mean temperature and how far it swings between summer and winter
+sex-ratios 'animal_name': Predict newborn sex ratios around the pivotal temperature of a reptile
+create-habitat 'habitat_name': Create a habitat, specifying biome, whether it's aquatic and its capacity
+habitats: List the habitats with how many animals they hold and of which kinds
+check-enclosures: List the habitats where a predator lives with its prey
+set-cohabitation 'strict|warn': Refuse or only warn about placing predators with their preys
+move 'id' 'habitat_name' 'reason': Move an animal alive to another habitat
+locations 'id': Show every habitat an animal has lived in
+simulate 'width'x'height': Step through a grid where the animals alive wander, hunting and breeding with their neighbours only
+capacity: Compare animals alive to the carrying capacity of their species and habitats, with the resulting birth chances
+infect 'animal_name': Infect an animal alive, spreading within its kind and optionally to its predators and preys
+outbreak: Show how many animals of each kind are susceptible, infected and recovered
+undo 'count': Undo the last changes made to species and animals, up to 20
+redo 'count': Redo the last changes undone
+replay 'date|all': Rebuild species and animals alive from the journal up to a date, checking a full replay against the files
+history 'animal_name|all' 'outcome|all' 'from..to|all': List what commands did, filtered by species, outcome like born, died or fed, and dates like 2026-03-01..2026-04-01
+event 'roll|log|table': Roll a random environmental event, list the ones that happened, or show the table they are rolled from
+set-lock-timeout 'seconds': Set how long commands wait for another terminal using the ecosystem, 5 by default
 help: Show this help"
         .to_string()
 }
@@ -1,20 +1,101 @@
 use std::error::Error;
+#[cfg(test)]
 use std::fs::File;
 use std::path::Path;
 
-use crate::custom_writers_and_readers::create_writer_truncate_for_path;
-use crate::{ANIMALS_ALIVE_FILE_PATH, ANIMALS_DATA_FILE_PATH, TEST_PATH};
+use crate::animal_alive_csv::{AnimalAliveReader, ANIMALS_ALIVE_HEADERS};
+use crate::animal_data_csv::{AnimalDataReader, ANIMALS_DATA_HEADERS};
+use crate::clock;
+use crate::custom_writers_and_readers::{
+    create_reader_for_path, create_writer_append_for_path, create_writer_truncate_for_path,
+};
+use crate::environmental_events_csv::{
+    DEFAULT_ENVIRONMENTAL_EVENTS, ENVIRONMENTAL_EVENTS_HEADERS, ENVIRONMENTAL_EVENTS_LOG_HEADERS,
+};
+use crate::file_lock::{FileLock, LockMode};
+use crate::habitats_csv::HABITATS_HEADERS;
+use crate::journal_csv::{snapshot_entries, JOURNAL_HEADERS};
+use crate::studbook_csv::STUDBOOK_HEADERS;
+use crate::temp_file_handler::{create_temp_file, sync_parent_directory};
+#[cfg(test)]
+use crate::test_path;
+use crate::transfers_csv::TRANSFERS_HEADERS;
+use crate::{
+    ANIMALS_ALIVE_FILE_PATH, ANIMALS_DATA_FILE_PATH, ENVIRONMENTAL_EVENTS_FILE_PATH,
+    ENVIRONMENTAL_EVENTS_LOG_FILE_PATH, HABITATS_FILE_PATH, JOURNAL_FILE_PATH, STUDBOOK_FILE_PATH,
+    TRANSFERS_FILE_PATH,
+};
 
 pub fn create_animals_data_if_not_exists() -> Result<(), Box<dyn Error>> {
-    create_csv_if_not_exists(
-        ANIMALS_DATA_FILE_PATH,
-        &["name", "class", "predators", "preys"],
-    )?;
+    create_csv_if_not_exists(ANIMALS_DATA_FILE_PATH, &ANIMALS_DATA_HEADERS)?;
     Ok(())
 }
 
 pub fn create_animals_alive_if_not_exists() -> Result<(), Box<dyn Error>> {
-    create_csv_if_not_exists(ANIMALS_ALIVE_FILE_PATH, &["name", "sex"])?;
+    create_csv_if_not_exists(ANIMALS_ALIVE_FILE_PATH, &ANIMALS_ALIVE_HEADERS)?;
+    Ok(())
+}
+
+pub fn create_studbook_if_not_exists() -> Result<(), Box<dyn Error>> {
+    create_csv_if_not_exists(STUDBOOK_FILE_PATH, &STUDBOOK_HEADERS)?;
+    Ok(())
+}
+
+pub fn create_habitats_if_not_exists() -> Result<(), Box<dyn Error>> {
+    create_csv_if_not_exists(HABITATS_FILE_PATH, &HABITATS_HEADERS)?;
+    Ok(())
+}
+
+pub fn create_transfers_if_not_exists() -> Result<(), Box<dyn Error>> {
+    create_csv_if_not_exists(TRANSFERS_FILE_PATH, &TRANSFERS_HEADERS)?;
+    Ok(())
+}
+
+/// Creates the journal, starting with a snapshot of the species and animals
+/// alive already there. It appears whole or not at all, so the snapshot is
+/// never cut short.
+pub fn create_journal_if_not_exists() -> Result<(), Box<dyn Error>> {
+    if Path::new(JOURNAL_FILE_PATH).exists() {
+        return Ok(());
+    }
+    let _lock = FileLock::acquire(LockMode::Exclusive)?;
+    // Another process may have created it while we waited.
+    if Path::new(JOURNAL_FILE_PATH).exists() {
+        return Ok(());
+    }
+    let entries = snapshot_entries(
+        &create_reader_for_path(ANIMALS_DATA_FILE_PATH)?.read_animals_data()?,
+        &create_reader_for_path(ANIMALS_ALIVE_FILE_PATH)?.read_animals_alive()?,
+        clock::now(),
+    );
+    let temp_file = create_temp_file(JOURNAL_FILE_PATH)?;
+    let temp_file_path = temp_file.path().to_str().ok_or("Invalid temp file path")?;
+    let mut writer = create_writer_append_for_path(temp_file_path)?;
+    writer.inner.write_record(JOURNAL_HEADERS)?;
+    for entry in &entries {
+        writer.write_journal_entry(entry)?;
+    }
+    writer.flush()?;
+    temp_file.persist(JOURNAL_FILE_PATH)?;
+    sync_parent_directory(JOURNAL_FILE_PATH)?;
+    Ok(())
+}
+
+/// Creates the table of environmental events, filled with a default one.
+pub fn create_environmental_events_if_not_exists() -> Result<(), Box<dyn Error>> {
+    if !Path::new(ENVIRONMENTAL_EVENTS_FILE_PATH).exists() {
+        let mut writer = create_writer_truncate_for_path(ENVIRONMENTAL_EVENTS_FILE_PATH)?;
+        writer.inner.write_record(ENVIRONMENTAL_EVENTS_HEADERS)?;
+        for event in DEFAULT_ENVIRONMENTAL_EVENTS {
+            writer.inner.write_record(event)?;
+        }
+        writer.flush()?;
+        sync_parent_directory(ENVIRONMENTAL_EVENTS_FILE_PATH)?;
+    }
+    create_csv_if_not_exists(
+        ENVIRONMENTAL_EVENTS_LOG_FILE_PATH,
+        &ENVIRONMENTAL_EVENTS_LOG_HEADERS,
+    )?;
     Ok(())
 }
 
@@ -25,25 +106,50 @@ fn create_csv_if_not_exists(filename: &str, headers: &[&str]) -> Result<(), Box<
         let mut writer = create_writer_truncate_for_path(filename)?;
 
         writer.inner.write_record(headers)?;
+        writer.flush()?;
+        sync_parent_directory(filename)?;
     }
     Ok(())
 }
 
+#[cfg(test)]
 pub fn create_test_csv(headers: &[&str]) -> Result<(), Box<dyn Error>> {
-    File::create(TEST_PATH)?;
+    File::create(test_path())?;
 
-    let mut writer = create_writer_truncate_for_path(TEST_PATH)?;
+    let mut writer = create_writer_truncate_for_path(&test_path())?;
 
     writer.inner.write_record(headers)?;
     Ok(())
 }
 
+#[cfg(test)]
 pub fn create_test_animals_alive() -> Result<(), Box<dyn Error>> {
-    create_test_csv(&["name", "sex"])
+    create_test_csv(&ANIMALS_ALIVE_HEADERS)
 }
 
+#[cfg(test)]
 pub fn create_test_animals_data() -> Result<(), Box<dyn Error>> {
-    create_test_csv(&["name", "class", "predators", "preys"])
+    create_test_csv(&ANIMALS_DATA_HEADERS)
+}
+
+#[cfg(test)]
+pub fn create_test_studbook() -> Result<(), Box<dyn Error>> {
+    create_test_csv(&STUDBOOK_HEADERS)
+}
+
+#[cfg(test)]
+pub fn create_test_habitats() -> Result<(), Box<dyn Error>> {
+    create_test_csv(&HABITATS_HEADERS)
+}
+
+#[cfg(test)]
+pub fn create_test_transfers() -> Result<(), Box<dyn Error>> {
+    create_test_csv(&TRANSFERS_HEADERS)
+}
+
+#[cfg(test)]
+pub fn create_test_journal() -> Result<(), Box<dyn Error>> {
+    create_test_csv(&JOURNAL_HEADERS)
 }
 
 #[cfg(test)]
@@ -55,27 +161,27 @@ mod tests {
 
     #[test]
     fn test_create_csv_if_not_exists() -> Result<(), Box<dyn Error>> {
-        create_csv_if_not_exists(TEST_PATH, &["header1", "header2"])?;
-        let mut reader = create_reader_for_path(TEST_PATH)?;
+        create_csv_if_not_exists(&test_path(), &["header1", "header2"])?;
+        let mut reader = create_reader_for_path(&test_path())?;
 
         for result in reader.inner.records() {
             let record = result?;
             assert_eq!(record, StringRecord::from(vec!["header1", "header2"]));
         }
-        remove_file(TEST_PATH)?;
+        remove_file(test_path())?;
         Ok(())
     }
 
     #[test]
     fn test_create_csv_for_testing() -> Result<(), Box<dyn Error>> {
         create_test_csv(&["header1", "header2"])?;
-        let mut reader = create_reader_for_path(TEST_PATH)?;
+        let mut reader = create_reader_for_path(&test_path())?;
 
         for result in reader.inner.records() {
             let record = result?;
             assert_eq!(record, StringRecord::from(vec!["header1", "header2"]));
         }
-        remove_file(TEST_PATH)?;
+        remove_file(test_path())?;
         Ok(())
     }
 }
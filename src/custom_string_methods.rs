@@ -1,9 +1,13 @@
 use std::error::Error;
 
-use crate::animal_structs::Class;
+use crate::animal_structs::{Class, ReproductionMode};
+use crate::clock::{SECONDS_PER_DAY, SECONDS_PER_HOUR, SECONDS_PER_WEEK, SECONDS_PER_YEAR};
 
 pub trait CustomStringMethods {
     fn to_class(&self) -> Result<Class, Box<dyn Error>>;
+    fn to_seconds(&self) -> Result<u64, Box<dyn Error>>;
+    fn to_days(&self) -> Result<u32, Box<dyn Error>>;
+    fn to_reproduction_mode(&self) -> Result<ReproductionMode, Box<dyn Error>>;
 }
 
 impl CustomStringMethods for String {
@@ -15,7 +19,69 @@ impl CustomStringMethods for String {
             "bird" => Ok(Class::Bird),
             "arthropod" => Ok(Class::Arthropod),
             "amphibian" => Ok(Class::Amphibian),
-            _ => return Err("Invalid class conversion".into()),
+            _ => Err("Invalid class conversion".into()),
         }
     }
+
+    fn to_seconds(&self) -> Result<u64, Box<dyn Error>> {
+        let (amount, unit) = self.split_at(self.len().saturating_sub(1));
+        let unit_seconds = match unit {
+            "h" => SECONDS_PER_HOUR,
+            "d" => SECONDS_PER_DAY,
+            "w" => SECONDS_PER_WEEK,
+            "y" => SECONDS_PER_YEAR,
+            _ => return Err("Invalid duration unit".into()),
+        };
+        amount
+            .parse::<u64>()?
+            .checked_mul(unit_seconds)
+            .ok_or_else(|| "Duration too long".into())
+    }
+
+    /// Duration rounded to the nearest day.
+    fn to_days(&self) -> Result<u32, Box<dyn Error>> {
+        let seconds = self.to_seconds()?;
+        let days =
+            seconds / SECONDS_PER_DAY + u64::from(seconds % SECONDS_PER_DAY >= SECONDS_PER_DAY / 2);
+        Ok(u32::try_from(days).map_err(|_| "Duration too long")?)
+    }
+
+    fn to_reproduction_mode(&self) -> Result<ReproductionMode, Box<dyn Error>> {
+        match self.as_str() {
+            "sexual" => Ok(ReproductionMode::Sexual),
+            "asexual" => Ok(ReproductionMode::Asexual),
+            "hermaphrodite" => Ok(ReproductionMode::Hermaphrodite),
+            "protandrous" => Ok(ReproductionMode::Protandrous),
+            "protogynous" => Ok(ReproductionMode::Protogynous),
+            _ => Err("Invalid reproduction mode conversion".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_seconds() -> Result<(), Box<dyn Error>> {
+        assert_eq!("12h".to_string().to_seconds()?, 12 * SECONDS_PER_HOUR);
+        assert_eq!("3d".to_string().to_seconds()?, 3 * SECONDS_PER_DAY);
+        assert_eq!("2w".to_string().to_seconds()?, 2 * SECONDS_PER_WEEK);
+        assert_eq!("1y".to_string().to_seconds()?, SECONDS_PER_YEAR);
+
+        assert!("12".to_string().to_seconds().is_err());
+        assert!("d".to_string().to_seconds().is_err());
+        assert!("3m".to_string().to_seconds().is_err());
+        assert!("999999999999y".to_string().to_seconds().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_days() -> Result<(), Box<dyn Error>> {
+        assert_eq!("2w".to_string().to_days()?, 14);
+        assert_eq!("36h".to_string().to_days()?, 2);
+        assert_eq!("11h".to_string().to_days()?, 0);
+        assert!("99999999y".to_string().to_days().is_err());
+        Ok(())
+    }
 }
@@ -1,16 +1,14 @@
-use csv::Position;
+use csv::{Position, StringRecord};
 use std::error::Error;
 use std::fs::{File, OpenOptions};
 use std::io;
 
-use crate::TEST_PATH;
+use crate::file_lock::{FileLock, LockMode};
+#[cfg(test)]
+use crate::test_path;
 
 fn file_write_append(path: &str) -> Result<File, io::Error> {
-    OpenOptions::new()
-        .write(true)
-        .create(true)
-        .append(true)
-        .open(path)
+    OpenOptions::new().create(true).append(true).open(path)
 }
 
 fn file_write_truncate(path: &str) -> Result<File, io::Error> {
@@ -21,30 +19,50 @@ fn file_write_truncate(path: &str) -> Result<File, io::Error> {
         .open(path)
 }
 
-pub struct CustomWriter<W: io::Write> {
+/// Storage whose writes can be forced to disk.
+pub trait Durable: io::Write {
+    fn sync(&self) -> io::Result<()>;
+}
+
+impl Durable for File {
+    fn sync(&self) -> io::Result<()> {
+        self.sync_data()
+    }
+}
+
+/// Holds the ecosystem exclusively while alive.
+pub struct CustomWriter<W: Durable> {
     pub inner: csv::Writer<W>,
+    _lock: FileLock,
 }
 
-impl<W: io::Write> CustomWriter<W> {
-    pub fn new(inner: csv::Writer<W>) -> Self {
-        CustomWriter { inner }
+impl<W: Durable> CustomWriter<W> {
+    pub fn new(inner: csv::Writer<W>, lock: FileLock) -> Self {
+        CustomWriter { inner, _lock: lock }
     }
 
+    /// Writes out buffered records and waits for them to reach the disk, so
+    /// a crash can't leave them half written.
     pub fn flush(&mut self) -> io::Result<()> {
-        self.inner.flush()
+        self.inner.flush()?;
+        self.inner.get_ref().sync()
     }
 }
 
+/// Holds a shared lock on the ecosystem while alive.
 pub struct CustomReader<R: std::io::Read + std::io::Seek> {
     pub inner: csv::Reader<R>,
+    _lock: FileLock,
 }
 
 impl<R: std::io::Read + std::io::Seek> CustomReader<R> {
-    pub fn new(inner: csv::Reader<R>) -> Self {
-        Self { inner }
+    pub fn new(inner: csv::Reader<R>, lock: FileLock) -> Self {
+        Self { inner, _lock: lock }
     }
     pub fn seek_to_beginning(&mut self) -> Result<(), Box<dyn Error>> {
         self.inner.seek(Position::new())?;
+        // Seeking forgets the headers were consumed, so skip them again.
+        self.inner.read_record(&mut StringRecord::new())?;
         Ok(())
     }
     pub fn count_animal(&mut self, animal_name: &str) -> Result<i32, Box<dyn Error>> {
@@ -60,26 +78,37 @@ impl<R: std::io::Read + std::io::Seek> CustomReader<R> {
     }
 }
 pub fn create_reader_for_path(path: &str) -> Result<CustomReader<File>, Box<dyn Error>> {
+    let lock = FileLock::acquire(LockMode::Shared)?;
     let file_read = File::open(path)?;
-    Ok(CustomReader::new(csv::Reader::from_reader(file_read)))
+    Ok(CustomReader::new(csv::Reader::from_reader(file_read), lock))
 }
 
 pub fn create_writer_append_for_path(path: &str) -> Result<CustomWriter<File>, Box<dyn Error>> {
+    let lock = FileLock::acquire(LockMode::Exclusive)?;
     let file_write = file_write_append(path)?;
-    Ok(CustomWriter::new(csv::Writer::from_writer(file_write)))
+    Ok(CustomWriter::new(
+        csv::Writer::from_writer(file_write),
+        lock,
+    ))
 }
 
 pub fn create_writer_truncate_for_path(path: &str) -> Result<CustomWriter<File>, Box<dyn Error>> {
+    let lock = FileLock::acquire(LockMode::Exclusive)?;
     let file_write = file_write_truncate(path)?;
-    Ok(CustomWriter::new(csv::Writer::from_writer(file_write)))
+    Ok(CustomWriter::new(
+        csv::Writer::from_writer(file_write),
+        lock,
+    ))
 }
 
+#[cfg(test)]
 pub fn reader_for_test() -> Result<CustomReader<File>, Box<dyn Error>> {
-    create_reader_for_path(TEST_PATH)
+    create_reader_for_path(&test_path())
 }
 
+#[cfg(test)]
 pub fn writer_for_test() -> Result<CustomWriter<File>, Box<dyn Error>> {
-    create_writer_append_for_path(TEST_PATH)
+    create_writer_append_for_path(&test_path())
 }
 
 #[cfg(test)]
@@ -92,24 +121,24 @@ mod tests {
     #[test]
     fn test_create_reader_for_path() -> Result<(), Box<dyn Error>> {
         create_test_csv(&["header1", "header2"])?;
-        let mut _reader = create_reader_for_path(TEST_PATH)?;
-        remove_file(TEST_PATH)?;
+        let mut _reader = create_reader_for_path(&test_path())?;
+        remove_file(test_path())?;
         Ok(())
     }
 
     #[test]
     fn test_create_writer_append_for_path() -> Result<(), Box<dyn Error>> {
         create_test_csv(&["header1", "header2"])?;
-        let mut _writer = create_writer_append_for_path(TEST_PATH)?;
-        remove_file(TEST_PATH)?;
+        let mut _writer = create_writer_append_for_path(&test_path())?;
+        remove_file(test_path())?;
         Ok(())
     }
 
     #[test]
     fn test_create_writer_truncate_for_path() -> Result<(), Box<dyn Error>> {
         create_test_csv(&["header1", "header2"])?;
-        let mut _writer = create_writer_truncate_for_path(TEST_PATH)?;
-        remove_file(TEST_PATH)?;
+        let mut _writer = create_writer_truncate_for_path(&test_path())?;
+        remove_file(test_path())?;
         Ok(())
     }
 }
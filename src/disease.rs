@@ -0,0 +1,190 @@
+use rand::prelude::Rng;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+
+use crate::animal_structs::*;
+use crate::temp_file_handler::write_file_durably;
+use crate::DISEASE_FILE_PATH;
+
+// Daily chances for a susceptible animal to catch the disease from each
+// infected animal of its species, for an infected one to recover, and to die.
+const TRANSMISSION_PER_DAY: f64 = 0.1;
+const RECOVERY_PER_DAY: f64 = 1.0 / 7.0;
+const DEATH_PER_DAY: f64 = 0.02;
+// How much less contagious a predator or prey is than an animal of the same
+// species.
+const CROSS_SPECIES_FACTOR: f64 = 0.3;
+
+/// State of the current outbreak, kept between commands.
+#[derive(PartialEq, Debug, Default)]
+pub struct Outbreak {
+    pub across_species: bool,
+    pub stepped_at: u64,
+    pub cases: u32,
+    pub deaths: u32,
+}
+
+impl Outbreak {
+    pub fn to_text(&self) -> String {
+        format!(
+            "{}:{}:{}:{}",
+            self.across_species, self.stepped_at, self.cases, self.deaths
+        )
+    }
+
+    pub fn from_text(text: &str) -> Option<Outbreak> {
+        let parts = text.trim().split(':').collect::<Vec<_>>();
+        let [across_species, stepped_at, cases, deaths] = parts[..] else {
+            return None;
+        };
+        Some(Outbreak {
+            across_species: across_species.parse().ok()?,
+            stepped_at: stepped_at.parse().ok()?,
+            cases: cases.parse().ok()?,
+            deaths: deaths.parse().ok()?,
+        })
+    }
+}
+
+pub fn outbreak() -> Outbreak {
+    fs::read_to_string(DISEASE_FILE_PATH)
+        .ok()
+        .and_then(|outbreak| Outbreak::from_text(&outbreak))
+        .unwrap_or_default()
+}
+
+pub fn set_outbreak(outbreak: &Outbreak) -> Result<(), Box<dyn Error>> {
+    write_file_durably(DISEASE_FILE_PATH, &outbreak.to_text())?;
+    Ok(())
+}
+
+/// What a day of outbreak did, by animal id.
+#[derive(PartialEq, Debug, Default)]
+pub struct DayOfOutbreak {
+    pub infected: Vec<u32>,
+    pub recovered: Vec<u32>,
+    pub died: Vec<u32>,
+}
+
+/// Lives a day of outbreak through: susceptible animals catch the disease
+/// from the infected ones of their species, and from their predators and
+/// preys if it spreads across species, while infected animals recover or
+/// die. Eggs are safe in their shell.
+pub fn spread_for_a_day<R: Rng>(
+    animals: &mut [AnimalAlive],
+    animals_data: &HashMap<String, AnimalData>,
+    across_species: bool,
+    rng: &mut R,
+) -> DayOfOutbreak {
+    let infected = animals
+        .iter()
+        .filter(|animal| animal.health() == Health::Infected)
+        .map(|animal| animal.name())
+        .collect::<Vec<_>>();
+    let mut day = DayOfOutbreak::default();
+    for animal in animals.iter_mut() {
+        match animal.health() {
+            Health::Susceptible if animal.stage() != LifeStage::Egg => {
+                let Some(animal_data) = animals_data.get(&animal.name()) else {
+                    continue;
+                };
+                let escape_chance = infected
+                    .iter()
+                    .map(|name| {
+                        if name == &animal.name() {
+                            1.0 - TRANSMISSION_PER_DAY
+                        } else if across_species
+                            && animals_data.get(name).is_some_and(|other| {
+                                other.hunts(animal_data) || animal_data.hunts(other)
+                            })
+                        {
+                            1.0 - TRANSMISSION_PER_DAY * CROSS_SPECIES_FACTOR
+                        } else {
+                            1.0
+                        }
+                    })
+                    .product::<f64>();
+                if !rng.gen_bool(escape_chance) {
+                    animal.health = Health::Infected;
+                    day.infected.push(animal.id());
+                }
+            }
+            Health::Infected if !day.infected.contains(&animal.id()) => {
+                if rng.gen_bool(DEATH_PER_DAY) {
+                    day.died.push(animal.id());
+                } else if rng.gen_bool(RECOVERY_PER_DAY) {
+                    animal.health = Health::Recovered;
+                    day.recovered.push(animal.id());
+                }
+            }
+            _ => {}
+        }
+    }
+    day
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::animal_structs::animals_alive_models::*;
+    use crate::animal_structs::animals_data_models::*;
+
+    fn animals_data() -> HashMap<String, AnimalData> {
+        [snake_data(), chameleon_data()]
+            .into_iter()
+            .map(|animal_data| (animal_data.name(), animal_data))
+            .collect()
+    }
+
+    /// Ids of the animals infected over `days`, burying the dead ones.
+    fn spread_for(
+        animals: &mut Vec<AnimalAlive>,
+        across_species: bool,
+        days: u32,
+        rng: &mut StdRng,
+    ) -> Vec<u32> {
+        let mut infected = Vec::new();
+        for _ in 0..days {
+            let day = spread_for_a_day(animals, &animals_data(), across_species, rng);
+            animals.retain(|animal| !day.died.contains(&animal.id()));
+            infected.extend(day.infected);
+        }
+        infected
+    }
+
+    #[test]
+    fn test_spread_within_species() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut animals = vec![snake_female(), snake_male(), chameleon_male()];
+        animals[0].health = Health::Infected;
+        let infected = spread_for(&mut animals, false, 200, &mut rng);
+        assert_eq!(infected, vec![2]);
+        assert!(animals
+            .iter()
+            .all(|animal| animal.health() != Health::Infected));
+    }
+
+    #[test]
+    fn test_spread_across_species() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut animals = vec![snake_female(), chameleon_male()];
+        animals[0].health = Health::Infected;
+        assert_eq!(spread_for(&mut animals, true, 200, &mut rng), vec![3]);
+    }
+
+    #[test]
+    fn test_outbreak_text_roundtrip() {
+        let outbreak = Outbreak {
+            across_species: true,
+            stepped_at: 42,
+            cases: 3,
+            deaths: 1,
+        };
+        assert_eq!(Outbreak::from_text(&outbreak.to_text()), Some(outbreak));
+        assert_eq!(Outbreak::from_text("true:42"), None);
+    }
+}
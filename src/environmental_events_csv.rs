@@ -0,0 +1,223 @@
+use csv::StringRecord;
+use rand::prelude::{Rng, SliceRandom};
+use std::error::Error;
+use std::fs::File;
+
+use crate::animal_structs::*;
+use crate::custom_string_methods::CustomStringMethods;
+use crate::custom_writers_and_readers::*;
+use crate::{ENVIRONMENTAL_EVENTS_FILE_PATH, ENVIRONMENTAL_EVENTS_LOG_FILE_PATH};
+
+pub const ENVIRONMENTAL_EVENTS_HEADERS: [&str; 4] = ["name", "weight", "target", "effect"];
+
+pub const ENVIRONMENTAL_EVENTS_LOG_HEADERS: [&str; 4] =
+    ["name", "applied_at", "affected", "summary"];
+
+/// Table written the first time, to be tuned by editing the file.
+pub const DEFAULT_ENVIRONMENTAL_EVENTS: [[&str; 4]; 6] = [
+    ["calm", "6", "all", "none"],
+    ["drought", "1", "biome:grassland", "starve:40"],
+    ["flood", "1", "class:arthropod", "kill:0.5"],
+    ["cold-snap", "1", "blood:cold", "kill:0.3"],
+    ["heatwave", "1", "blood:warm", "starve:30"],
+    ["epizootic", "1", "all", "kill:0.1"],
+];
+
+/// Targets are written like `all`, `class:arthropod`, `blood:cold`,
+/// `biome:wetland`, `habitat:reef` or `species:rat`.
+pub fn event_target_to_csv(target: &EventTarget) -> String {
+    match target {
+        EventTarget::All => "all".to_string(),
+        EventTarget::Class(class) => format!("class:{}", class.as_str()),
+        EventTarget::BloodType(BloodType::Warm) => "blood:warm".to_string(),
+        EventTarget::BloodType(BloodType::Cold) => "blood:cold".to_string(),
+        EventTarget::Biome(biome) => format!("biome:{}", biome),
+        EventTarget::Habitat(habitat) => format!("habitat:{}", habitat),
+        EventTarget::Species(species) => format!("species:{}", species),
+    }
+}
+
+pub fn event_target_from_csv(field: &str) -> Result<EventTarget, Box<dyn Error>> {
+    if field == "all" {
+        return Ok(EventTarget::All);
+    }
+    let (kind, value) = field.split_once(':').ok_or("Invalid event target")?;
+    match (kind, value) {
+        ("class", class) => Ok(EventTarget::Class(class.to_string().to_class()?)),
+        ("blood", "warm") => Ok(EventTarget::BloodType(BloodType::Warm)),
+        ("blood", "cold") => Ok(EventTarget::BloodType(BloodType::Cold)),
+        ("biome", biome) => Ok(EventTarget::Biome(biome.to_string())),
+        ("habitat", habitat) => Ok(EventTarget::Habitat(habitat.to_string())),
+        ("species", species) => Ok(EventTarget::Species(species.to_string())),
+        _ => Err("Invalid event target".into()),
+    }
+}
+
+/// Effects are written like `none`, `kill:0.3`, `starve:40` or `migrate:3`.
+pub fn event_effect_to_csv(effect: &EventEffect) -> String {
+    match effect {
+        EventEffect::Nothing => "none".to_string(),
+        EventEffect::Kill(chance) => format!("kill:{}", chance),
+        EventEffect::Starve(energy) => format!("starve:{}", energy),
+        EventEffect::Migrate(count) => format!("migrate:{}", count),
+    }
+}
+
+pub fn event_effect_from_csv(field: &str) -> Result<EventEffect, Box<dyn Error>> {
+    if field == "none" {
+        return Ok(EventEffect::Nothing);
+    }
+    let (kind, amount) = field.split_once(':').ok_or("Invalid event effect")?;
+    match kind {
+        "kill" => {
+            let chance: f64 = amount.parse()?;
+            if !(0.0..=1.0).contains(&chance) {
+                return Err("Kill chance must be between 0 and 1".into());
+            }
+            Ok(EventEffect::Kill(chance))
+        }
+        "starve" => Ok(EventEffect::Starve(amount.parse()?)),
+        "migrate" => Ok(EventEffect::Migrate(amount.parse()?)),
+        _ => Err("Invalid event effect".into()),
+    }
+}
+
+impl EnvironmentalEvent {
+    pub fn to_csv(&self) -> [String; 4] {
+        [
+            self.name.clone(),
+            self.weight.to_string(),
+            event_target_to_csv(&self.target),
+            event_effect_to_csv(&self.effect),
+        ]
+    }
+
+    pub fn from_csv(record: &StringRecord) -> Result<EnvironmentalEvent, Box<dyn Error>> {
+        Ok(EnvironmentalEvent {
+            name: record[0].to_string(),
+            weight: record[1].parse()?,
+            target: event_target_from_csv(&record[2])?,
+            effect: event_effect_from_csv(&record[3])?,
+        })
+    }
+}
+
+impl AppliedEvent {
+    pub fn to_csv(&self) -> [String; 4] {
+        [
+            self.name.clone(),
+            self.applied_at.to_string(),
+            self.affected.to_string(),
+            self.summary.clone(),
+        ]
+    }
+
+    pub fn from_csv(record: &StringRecord) -> Result<AppliedEvent, Box<dyn Error>> {
+        Ok(AppliedEvent {
+            name: record[0].to_string(),
+            applied_at: record[1].parse()?,
+            affected: record[2].parse()?,
+            summary: record[3].to_string(),
+        })
+    }
+}
+
+/// Picks an event at random, heavier weights being likelier.
+pub fn roll_event<'a, R: Rng>(
+    events: &'a [EnvironmentalEvent],
+    rng: &mut R,
+) -> Option<&'a EnvironmentalEvent> {
+    events.choose_weighted(rng, |event| event.weight).ok()
+}
+
+impl<W: Durable> CustomWriter<W> {
+    pub fn write_applied_event(&mut self, event: &AppliedEvent) -> csv::Result<()> {
+        self.inner.write_record(event.to_csv())?;
+        self.flush()?;
+        Ok(())
+    }
+}
+
+pub trait EnvironmentalEventsReader {
+    fn read_environmental_events(&mut self) -> Result<Vec<EnvironmentalEvent>, Box<dyn Error>>;
+    fn read_applied_events(&mut self) -> Result<Vec<AppliedEvent>, Box<dyn Error>>;
+}
+
+impl<R: std::io::Read + std::io::Seek> EnvironmentalEventsReader for CustomReader<R> {
+    fn read_environmental_events(&mut self) -> Result<Vec<EnvironmentalEvent>, Box<dyn Error>> {
+        let mut events = Vec::new();
+        for result in self.inner.records() {
+            events.push(EnvironmentalEvent::from_csv(&result?)?);
+        }
+        self.seek_to_beginning()?;
+        Ok(events)
+    }
+    fn read_applied_events(&mut self) -> Result<Vec<AppliedEvent>, Box<dyn Error>> {
+        let mut events = Vec::new();
+        for result in self.inner.records() {
+            events.push(AppliedEvent::from_csv(&result?)?);
+        }
+        self.seek_to_beginning()?;
+        Ok(events)
+    }
+}
+
+pub fn reader_environmental_events() -> Result<CustomReader<File>, Box<dyn Error>> {
+    create_reader_for_path(ENVIRONMENTAL_EVENTS_FILE_PATH)
+}
+
+pub fn writer_environmental_events_log() -> Result<CustomWriter<File>, Box<dyn Error>> {
+    create_writer_append_for_path(ENVIRONMENTAL_EVENTS_LOG_FILE_PATH)
+}
+
+pub fn reader_environmental_events_log() -> Result<CustomReader<File>, Box<dyn Error>> {
+    create_reader_for_path(ENVIRONMENTAL_EVENTS_LOG_FILE_PATH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_default_events_parse() -> Result<(), Box<dyn Error>> {
+        for row in DEFAULT_ENVIRONMENTAL_EVENTS {
+            let event = EnvironmentalEvent::from_csv(&StringRecord::from(row.to_vec()))?;
+            assert_eq!(event.to_csv().to_vec(), row.to_vec());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_event_target_and_effect_csv() -> Result<(), Box<dyn Error>> {
+        assert_eq!(
+            event_target_from_csv("class:arthropod")?,
+            EventTarget::Class(Class::Arthropod)
+        );
+        assert_eq!(
+            event_target_from_csv("species:rat")?,
+            EventTarget::Species("rat".to_string())
+        );
+        assert!(event_target_from_csv("blood:lukewarm").is_err());
+        assert_eq!(event_effect_from_csv("migrate:3")?, EventEffect::Migrate(3));
+        assert!(event_effect_from_csv("kill:2").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_roll_event() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let event = |name: &str, weight| EnvironmentalEvent {
+            name: name.to_string(),
+            weight,
+            target: EventTarget::All,
+            effect: EventEffect::Nothing,
+        };
+        let events = vec![event("never", 0), event("always", 1)];
+        for _ in 0..10 {
+            assert_eq!(roll_event(&events, &mut rng).unwrap().name, "always");
+        }
+        assert!(roll_event(&[], &mut rng).is_none());
+    }
+}
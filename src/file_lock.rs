@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::temp_file_handler::write_file_durably;
+use crate::{LOCK_FILE_PATH, LOCK_TIMEOUT_FILE_PATH};
+
+pub const DEFAULT_LOCK_TIMEOUT_SECONDS: u64 = 5;
+
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Returned when another process kept the ecosystem locked for longer than
+/// the lock timeout.
+#[derive(Debug)]
+pub struct EcosystemBusy;
+
+impl fmt::Display for EcosystemBusy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The ecosystem is busy in another terminal, try again or wait longer with set-lock-timeout"
+        )
+    }
+}
+
+impl Error for EcosystemBusy {}
+
+/// Seconds to wait for another process to release the ecosystem.
+pub fn lock_timeout() -> u64 {
+    fs::read_to_string(LOCK_TIMEOUT_FILE_PATH)
+        .ok()
+        .and_then(|seconds| seconds.trim().parse().ok())
+        .unwrap_or(DEFAULT_LOCK_TIMEOUT_SECONDS)
+}
+
+pub fn set_lock_timeout(seconds: u64) -> Result<(), Box<dyn Error>> {
+    write_file_durably(LOCK_TIMEOUT_FILE_PATH, &seconds.to_string())?;
+    Ok(())
+}
+
+/// Turns a busy ecosystem into a message for the user, other errors being
+/// left to stop the program.
+pub fn report_busy(output: Result<String, Box<dyn Error>>) -> Result<String, Box<dyn Error>> {
+    match output {
+        Err(error) if error.is::<EcosystemBusy>() => Ok(error.to_string()),
+        output => output,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LockMode {
+    Shared,
+    Exclusive,
+}
+
+/// Lock file opened by this process, with how many guards use it. Locks
+/// belong to the open file, so readers and writers of the same process
+/// share one and never wait for each other.
+struct HeldLock {
+    file: File,
+    shared: usize,
+    exclusive: usize,
+}
+
+static HELD_LOCKS: Mutex<BTreeMap<String, HeldLock>> = Mutex::new(BTreeMap::new());
+
+/// Advisory lock on the ecosystem, shared by readers and exclusive to
+/// writers across processes, released when dropped.
+pub struct FileLock {
+    path: String,
+    mode: LockMode,
+}
+
+impl FileLock {
+    pub fn acquire(mode: LockMode) -> Result<FileLock, Box<dyn Error>> {
+        FileLock::acquire_for_path(LOCK_FILE_PATH, mode, Duration::from_secs(lock_timeout()))
+    }
+
+    pub(crate) fn acquire_for_path(
+        path: &str,
+        mode: LockMode,
+        timeout: Duration,
+    ) -> Result<FileLock, Box<dyn Error>> {
+        let mut held_locks = HELD_LOCKS.lock().map_err(|_| "Lock state poisoned")?;
+        let mut opened = false;
+        if !held_locks.contains_key(path) {
+            let file = OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(path)?;
+            // Other threads lock and unlock meanwhile, rather than waiting
+            // on another process along with us.
+            drop(held_locks);
+            wait_for(&file, mode, timeout)?;
+            held_locks = HELD_LOCKS.lock().map_err(|_| "Lock state poisoned")?;
+            // Should a thread have got in first, our file closes, and its lock is
+            // joined below.
+            if !held_locks.contains_key(path) {
+                held_locks.insert(
+                    path.to_string(),
+                    HeldLock {
+                        file,
+                        shared: 0,
+                        exclusive: 0,
+                    },
+                );
+                opened = true;
+            }
+        }
+        let held_lock = held_locks.get_mut(path).ok_or("Lock vanished")?;
+        if !opened && mode == LockMode::Exclusive && held_lock.exclusive == 0 {
+            // Two processes waiting to upgrade would each wait on the other's
+            // shared lock, so upgrades fail at once. Commands that write take
+            // the exclusive lock up front and never need one.
+            if let Err(error) = wait_for(&held_lock.file, mode, Duration::ZERO) {
+                // A failed upgrade can drop the shared lock, so take it back,
+                // or forget the file, so that the next guard waits for a lock
+                // of its own.
+                if let Err(relock_error) = held_lock.file.try_lock_shared() {
+                    held_locks.remove(path);
+                    return Err(relock_error.into());
+                }
+                return Err(error);
+            }
+        }
+        match mode {
+            LockMode::Shared => held_lock.shared += 1,
+            LockMode::Exclusive => held_lock.exclusive += 1,
+        }
+        Ok(FileLock {
+            path: path.to_string(),
+            mode,
+        })
+    }
+
+    pub fn mode(&self) -> LockMode {
+        self.mode
+    }
+}
+
+fn wait_for(file: &File, mode: LockMode, timeout: Duration) -> Result<(), Box<dyn Error>> {
+    let started = Instant::now();
+    loop {
+        let attempt = match mode {
+            LockMode::Shared => file.try_lock_shared(),
+            LockMode::Exclusive => file.try_lock(),
+        };
+        match attempt {
+            Ok(()) => return Ok(()),
+            Err(TryLockError::WouldBlock) if started.elapsed() < timeout => {
+                thread::sleep(RETRY_INTERVAL)
+            }
+            Err(TryLockError::WouldBlock) => return Err(Box::new(EcosystemBusy)),
+            Err(TryLockError::Error(error)) => return Err(error.into()),
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let Ok(mut held_locks) = HELD_LOCKS.lock() else {
+            return;
+        };
+        let Some(held_lock) = held_locks.get_mut(&self.path) else {
+            return;
+        };
+        match self.mode {
+            LockMode::Shared => held_lock.shared -= 1,
+            LockMode::Exclusive => held_lock.exclusive -= 1,
+        }
+        if held_lock.shared == 0 && held_lock.exclusive == 0 {
+            // Closing the file releases the lock.
+            held_locks.remove(&self.path);
+        } else if self.mode == LockMode::Exclusive
+            && held_lock.exclusive == 0
+            && held_lock.file.try_lock_shared().is_err()
+        {
+            // Another process got in first, so forget the file, and the next
+            // guard waits for a lock of its own.
+            held_locks.remove(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_locks_wait_for_other_processes() -> Result<(), Box<dyn Error>> {
+        let dir = tempdir()?;
+        let path = dir.path().join("ecosystem.lock");
+        let path = path.to_str().unwrap();
+        let timeout = Duration::ZERO;
+
+        let reader = FileLock::acquire_for_path(path, LockMode::Shared, timeout)?;
+        let writer = FileLock::acquire_for_path(path, LockMode::Exclusive, timeout)?;
+
+        // Another open file stands for another process.
+        let other = File::open(path)?;
+        assert!(matches!(
+            other.try_lock_shared(),
+            Err(TryLockError::WouldBlock)
+        ));
+        drop(writer);
+        other.try_lock_shared()?;
+        let error = FileLock::acquire_for_path(path, LockMode::Exclusive, timeout).err();
+        assert!(error.is_some_and(|error| error.is::<EcosystemBusy>()));
+        other.unlock()?;
+
+        drop(reader);
+        other.try_lock()?;
+        let error = FileLock::acquire_for_path(path, LockMode::Shared, timeout).err();
+        assert!(error.is_some_and(|error| error.is::<EcosystemBusy>()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_upgrades_fail_at_once() -> Result<(), Box<dyn Error>> {
+        let dir = tempdir()?;
+        let path = dir.path().join("ecosystem.lock");
+        let path = path.to_str().unwrap();
+        let reader = FileLock::acquire_for_path(path, LockMode::Shared, Duration::ZERO)?;
+        let other = File::open(path)?;
+        other.try_lock_shared()?;
+
+        let started = Instant::now();
+        let timeout = Duration::from_secs(60);
+        let error = FileLock::acquire_for_path(path, LockMode::Exclusive, timeout).err();
+        assert!(error.is_some_and(|error| error.is::<EcosystemBusy>()));
+        assert!(started.elapsed() < timeout);
+        // The shared lock is kept.
+        assert!(matches!(other.try_lock(), Err(TryLockError::WouldBlock)));
+        drop(reader);
+        Ok(())
+    }
+
+    #[test]
+    fn test_waiting_holds_up_no_other_lock() -> Result<(), Box<dyn Error>> {
+        let dir = tempdir()?;
+        let busy_path = dir.path().join("busy.lock").to_string_lossy().into_owned();
+        let free_path = dir.path().join("free.lock").to_string_lossy().into_owned();
+        let other = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&busy_path)?;
+        other.try_lock()?;
+
+        let waiting = thread::spawn(move || {
+            FileLock::acquire_for_path(&busy_path, LockMode::Shared, Duration::from_secs(60))
+                .map(drop)
+                .map_err(|error| error.to_string())
+        });
+        thread::sleep(RETRY_INTERVAL * 2);
+        let started = Instant::now();
+        drop(FileLock::acquire_for_path(
+            &free_path,
+            LockMode::Exclusive,
+            Duration::ZERO,
+        )?);
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        other.unlock()?;
+        assert_eq!(waiting.join().unwrap(), Ok(()));
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use rand::prelude::{Rng, SliceRandom};
+use std::collections::BTreeMap;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Dominance {
+    /// Heterozygotes show the dominant allele only.
+    Complete,
+    /// Heterozygotes show both alleles.
+    Codominant,
+}
+
+/// A gene with two alleles, like the colour morph of a snake.
+#[derive(PartialEq, Debug, Clone)]
+pub struct HeritableTrait {
+    pub name: String,
+    pub dominant: String,
+    pub recessive: String,
+    pub dominance: Dominance,
+}
+
+impl HeritableTrait {
+    pub fn new(name: &str, dominant: &str, recessive: &str, dominance: Dominance) -> Self {
+        HeritableTrait {
+            name: name.to_string(),
+            dominant: dominant.to_string(),
+            recessive: recessive.to_string(),
+            dominance,
+        }
+    }
+
+    pub fn alleles(&self) -> [&String; 2] {
+        [&self.dominant, &self.recessive]
+    }
+
+    pub fn phenotype(&self, alleles: &(String, String)) -> String {
+        let (first, second) = alleles;
+        if first == second {
+            first.clone()
+        } else {
+            match self.dominance {
+                Dominance::Complete => self.dominant.clone(),
+                Dominance::Codominant => format!("{}-{}", self.dominant, self.recessive),
+            }
+        }
+    }
+}
+
+/// The pair of alleles an animal carries for each trait of its kind.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct Genotype {
+    pub alleles: BTreeMap<String, (String, String)>,
+}
+
+impl Genotype {
+    /// Genotype of a founder, with alleles picked at random.
+    pub fn random<R: Rng>(traits: &[HeritableTrait], rng: &mut R) -> Self {
+        let alleles = traits
+            .iter()
+            .map(|heritable_trait| {
+                let mut allele = || (*heritable_trait.alleles().choose(rng).unwrap()).clone();
+                (heritable_trait.name.clone(), (allele(), allele()))
+            })
+            .collect();
+        Genotype { alleles }
+    }
+
+    /// Genotype of an offspring, taking one allele of each parent per trait.
+    pub fn inherit<R: Rng>(mother: &Genotype, father: &Genotype, rng: &mut R) -> Self {
+        let mut gamete = |(first, second): &(String, String)| {
+            if rng.gen_bool(0.5) {
+                first.clone()
+            } else {
+                second.clone()
+            }
+        };
+        let alleles = mother
+            .alleles
+            .iter()
+            .filter_map(|(name, from_mother)| {
+                let from_father = father.alleles.get(name)?;
+                Some((name.clone(), (gamete(from_mother), gamete(from_father))))
+            })
+            .collect();
+        Genotype { alleles }
+    }
+
+    pub fn describe(&self, traits: &[HeritableTrait]) -> String {
+        traits
+            .iter()
+            .filter_map(|heritable_trait| {
+                let alleles = self.alleles.get(&heritable_trait.name)?;
+                Some(format!(
+                    "{} {} ({}/{})",
+                    heritable_trait.name,
+                    heritable_trait.phenotype(alleles),
+                    alleles.0,
+                    alleles.1
+                ))
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Share of each allele of `heritable_trait` among `genotypes`.
+pub fn allele_frequencies(
+    heritable_trait: &HeritableTrait,
+    genotypes: &[&Genotype],
+) -> Vec<(String, f64)> {
+    let mut counts = BTreeMap::new();
+    let mut total = 0;
+    for alleles in genotypes
+        .iter()
+        .filter_map(|genotype| genotype.alleles.get(&heritable_trait.name))
+    {
+        for allele in [&alleles.0, &alleles.1] {
+            *counts.entry(allele.clone()).or_insert(0) += 1;
+            total += 1;
+        }
+    }
+    heritable_trait
+        .alleles()
+        .into_iter()
+        .map(|allele| {
+            let count = counts.get(allele).copied().unwrap_or(0);
+            let frequency = if total == 0 {
+                0.0
+            } else {
+                count as f64 / total as f64
+            };
+            (allele.clone(), frequency)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+
+    fn color() -> HeritableTrait {
+        HeritableTrait::new("color", "green", "albino", Dominance::Complete)
+    }
+
+    fn genotype(first: &str, second: &str) -> Genotype {
+        Genotype {
+            alleles: BTreeMap::from([(
+                "color".to_string(),
+                (first.to_string(), second.to_string()),
+            )]),
+        }
+    }
+
+    #[test]
+    fn test_phenotype() {
+        let carrier = ("green".to_string(), "albino".to_string());
+        assert_eq!(color().phenotype(&carrier), "green");
+        assert_eq!(
+            color().phenotype(&("albino".to_string(), "albino".to_string())),
+            "albino"
+        );
+
+        let size = HeritableTrait::new("size", "large", "small", Dominance::Codominant);
+        assert_eq!(
+            size.phenotype(&("small".to_string(), "large".to_string())),
+            "large-small"
+        );
+    }
+
+    #[test]
+    fn test_inherit() {
+        let mut rng = thread_rng();
+        let albino = genotype("albino", "albino");
+        let green = genotype("green", "green");
+        assert_eq!(
+            Genotype::inherit(&albino, &albino, &mut rng),
+            genotype("albino", "albino")
+        );
+        assert_eq!(
+            Genotype::inherit(&albino, &green, &mut rng),
+            genotype("albino", "green")
+        );
+    }
+
+    #[test]
+    fn test_allele_frequencies() {
+        let genotypes = [&genotype("green", "albino"), &genotype("albino", "albino")];
+        assert_eq!(
+            allele_frequencies(&color(), &genotypes),
+            vec![("green".to_string(), 0.25), ("albino".to_string(), 0.75)]
+        );
+    }
+}
@@ -0,0 +1,344 @@
+use rand::prelude::{Rng, SliceRandom};
+use std::collections::HashMap;
+
+use crate::animal_structs::*;
+use crate::genetics::Genotype;
+
+// Chance per tick that a mother next to a father gives birth, and how many
+// ticks she then rests.
+const BIRTH_CHANCE: f64 = 0.3;
+const REST_TICKS: u32 = 5;
+
+/// An animal standing on a cell of the grid.
+#[derive(Debug)]
+pub struct Creature {
+    pub animal: AnimalAlive,
+    pub x: usize,
+    pub y: usize,
+    rest: u32,
+}
+
+impl Creature {
+    fn is_neighbour_of(&self, other: &Creature) -> bool {
+        self.x.abs_diff(other.x) <= 1 && self.y.abs_diff(other.y) <= 1
+    }
+}
+
+/// Teaching variant of the ecosystem where animals wander a grid, and only
+/// hunt and breed with the animals next to them.
+pub struct Grid {
+    width: usize,
+    height: usize,
+    creatures: Vec<Creature>,
+    species: HashMap<String, AnimalData>,
+    tick: u32,
+    next_id: u32,
+}
+
+impl Grid {
+    /// Scatters the animals at random over a `width` by `height` grid.
+    pub fn populate<R: Rng>(
+        width: usize,
+        height: usize,
+        animals: Vec<AnimalAlive>,
+        species: HashMap<String, AnimalData>,
+        rng: &mut R,
+    ) -> Grid {
+        let next_id = animals.iter().map(|animal| animal.id()).max().unwrap_or(0) + 1;
+        let creatures = animals
+            .into_iter()
+            .filter(|animal| species.contains_key(&animal.name()))
+            .map(|animal| Creature {
+                animal,
+                x: rng.gen_range(0..width),
+                y: rng.gen_range(0..height),
+                rest: 0,
+            })
+            .collect();
+        Grid {
+            width,
+            height,
+            creatures,
+            species,
+            tick: 0,
+            next_id,
+        }
+    }
+
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    pub fn creatures(&self) -> &[Creature] {
+        &self.creatures
+    }
+
+    /// Moves everyone one cell at most, then lets neighbours hunt and breed,
+    /// returning what happened.
+    pub fn step<R: Rng>(&mut self, rng: &mut R) -> Vec<String> {
+        self.tick += 1;
+        for creature in &mut self.creatures {
+            creature.x = wander(creature.x, self.width, rng);
+            creature.y = wander(creature.y, self.height, rng);
+            creature.rest = creature.rest.saturating_sub(1);
+        }
+        let mut events = self.hunt(rng);
+        events.extend(self.breed(rng));
+        events
+    }
+
+    fn hunt<R: Rng>(&mut self, rng: &mut R) -> Vec<String> {
+        let mut order = (0..self.creatures.len()).collect::<Vec<_>>();
+        order.shuffle(rng);
+        let mut eaten = vec![false; self.creatures.len()];
+        let mut events = Vec::new();
+        for predator in order {
+            if eaten[predator] {
+                continue;
+            }
+            let hunter = &self.creatures[predator];
+            let prey = (0..self.creatures.len()).find(|&prey| {
+                let target = &self.creatures[prey];
+                !eaten[prey]
+                    && hunter.is_neighbour_of(target)
+                    && self.species[&hunter.animal.name()]
+                        .hunts(&self.species[&target.animal.name()])
+            });
+            if let Some(prey) = prey {
+                eaten[prey] = true;
+                let target = &self.creatures[prey];
+                events.push(format!(
+                    "Tick {}: {} #{} ate {} #{} at ({}, {})",
+                    self.tick,
+                    hunter.animal.name(),
+                    hunter.animal.id(),
+                    target.animal.name(),
+                    target.animal.id(),
+                    target.x,
+                    target.y
+                ));
+            }
+        }
+        let mut index = 0;
+        self.creatures.retain(|_| {
+            index += 1;
+            !eaten[index - 1]
+        });
+        events
+    }
+
+    fn breed<R: Rng>(&mut self, rng: &mut R) -> Vec<String> {
+        let mut newborns = Vec::new();
+        let mut events = Vec::new();
+        for mother in 0..self.creatures.len() {
+            let animal_data = &self.species[&self.creatures[mother].animal.name()];
+            let (mother_sex, father_sex) = animal_data.reproduction.parent_sexes();
+            let is_ready = |creature: &Creature, sex: Sex| {
+                creature.animal.name() == animal_data.name
+                    && creature.animal.sex() == &sex
+                    && creature.animal.is_adult()
+                    && creature.rest == 0
+            };
+            if !is_ready(&self.creatures[mother], mother_sex) {
+                continue;
+            }
+            let father = match father_sex {
+                Some(father_sex) => {
+                    match (0..self.creatures.len()).find(|&father| {
+                        father != mother
+                            && is_ready(&self.creatures[father], father_sex)
+                            && self.creatures[mother].is_neighbour_of(&self.creatures[father])
+                    }) {
+                        Some(father) => Some(father),
+                        None => continue,
+                    }
+                }
+                None => None,
+            };
+            if !rng.gen_bool(BIRTH_CHANCE) {
+                continue;
+            }
+            let parent = &self.creatures[mother];
+            // Newborns are adults straight away to keep the simulation lively.
+            let mut animal = AnimalAlive::born(
+                self.next_id,
+                &animal_data.name,
+                *animal_data
+                    .reproduction
+                    .offspring_sexes()
+                    .choose(rng)
+                    .unwrap(),
+            );
+            animal.genotype = match father {
+                Some(father) => Genotype::inherit(
+                    parent.animal.genotype(),
+                    self.creatures[father].animal.genotype(),
+                    rng,
+                ),
+                None => parent.animal.genotype().clone(),
+            };
+            animal.habitat = parent.animal.habitat().to_string();
+            events.push(format!(
+                "Tick {}: {} #{} was born at ({}, {})",
+                self.tick,
+                animal.name(),
+                animal.id(),
+                parent.x,
+                parent.y
+            ));
+            newborns.push(Creature {
+                animal,
+                x: parent.x,
+                y: parent.y,
+                rest: REST_TICKS,
+            });
+            self.next_id += 1;
+            self.creatures[mother].rest = REST_TICKS;
+            if let Some(father) = father {
+                self.creatures[father].rest = REST_TICKS;
+            }
+        }
+        self.creatures.extend(newborns);
+        events
+    }
+
+    /// Letter standing for each species on the rendered grid: the first
+    /// letter of its name not taken yet, in lower or upper case.
+    fn symbols(&self) -> HashMap<String, char> {
+        let mut names = self.species.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        let mut symbols = HashMap::new();
+        for name in names {
+            let symbol = name
+                .chars()
+                .filter(|letter| letter.is_ascii_alphabetic())
+                .flat_map(|letter| [letter, letter.to_ascii_uppercase()])
+                .chain('0'..='9')
+                .find(|symbol| !symbols.values().any(|taken| taken == symbol))
+                .unwrap_or('?');
+            symbols.insert(name, symbol);
+        }
+        symbols
+    }
+
+    /// ASCII picture of the grid: a letter per species, `*` where several
+    /// animals share a cell, and a legend with counts.
+    pub fn render(&self) -> String {
+        let symbols = self.symbols();
+        let mut cells = vec![vec!['.'; self.width]; self.height];
+        let mut counts = HashMap::new();
+        for creature in &self.creatures {
+            let cell = &mut cells[creature.y][creature.x];
+            *cell = if *cell == '.' {
+                symbols[&creature.animal.name()]
+            } else {
+                '*'
+            };
+            *counts.entry(creature.animal.name()).or_insert(0) += 1;
+        }
+        let border = format!("+{}+", "-".repeat(self.width));
+        let mut lines = vec![format!("Tick {}", self.tick), border.clone()];
+        for row in cells {
+            lines.push(format!("|{}|", row.into_iter().collect::<String>()));
+        }
+        lines.push(border);
+        let mut legend = symbols.into_iter().collect::<Vec<_>>();
+        legend.sort();
+        for (name, symbol) in legend {
+            lines.push(format!(
+                "{} {}: {}",
+                symbol,
+                name,
+                counts.get(&name).unwrap_or(&0)
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+fn wander<R: Rng>(position: usize, size: usize, rng: &mut R) -> usize {
+    match rng.gen_range(-1..=1) {
+        -1 => position.saturating_sub(1),
+        1 => (position + 1).min(size - 1),
+        _ => position,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::animal_structs::animals_alive_models::*;
+    use crate::animal_structs::animals_data_models::*;
+
+    fn grid(width: usize, height: usize, placed: Vec<(AnimalAlive, usize, usize)>) -> Grid {
+        let species = [snake_data(), chameleon_data()]
+            .into_iter()
+            .map(|animal_data| (animal_data.name(), animal_data))
+            .collect();
+        let mut grid = Grid::populate(
+            width,
+            height,
+            vec![],
+            species,
+            &mut StdRng::seed_from_u64(0),
+        );
+        grid.creatures = placed
+            .into_iter()
+            .map(|(animal, x, y)| Creature {
+                animal,
+                x,
+                y,
+                rest: 0,
+            })
+            .collect();
+        grid.next_id = 10;
+        grid
+    }
+
+    #[test]
+    fn test_render() {
+        let grid = grid(
+            3,
+            2,
+            vec![
+                (snake_female(), 0, 0),
+                (snake_male(), 2, 1),
+                (chameleon_male(), 2, 1),
+            ],
+        );
+        assert_eq!(
+            grid.render(),
+            "Tick 0\n+---+\n|s..|\n|..*|\n+---+\nc chameleon: 1\ns snake: 2"
+        );
+    }
+
+    #[test]
+    fn test_hunt_neighbours_only() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut far = grid(10, 10, vec![(snake_male(), 0, 0), (chameleon_male(), 9, 9)]);
+        assert!(far.hunt(&mut rng).is_empty());
+        assert_eq!(far.creatures().len(), 2);
+
+        let mut near = grid(10, 10, vec![(snake_male(), 4, 4), (chameleon_male(), 5, 5)]);
+        assert_eq!(near.hunt(&mut rng).len(), 1);
+        assert_eq!(near.creatures()[0].animal.name(), "snake");
+        assert_eq!(near.creatures().len(), 1);
+    }
+
+    #[test]
+    fn test_breed_neighbours_only() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut far = grid(10, 10, vec![(snake_female(), 0, 0), (snake_male(), 9, 9)]);
+        for _ in 0..20 {
+            assert!(far.breed(&mut rng).is_empty());
+        }
+
+        let mut near = grid(10, 10, vec![(snake_female(), 3, 3), (snake_male(), 3, 4)]);
+        let births = (0..20).map(|_| near.breed(&mut rng).len()).sum::<usize>();
+        assert!(births > 0);
+        assert_eq!(near.creatures().len(), 2 + births);
+    }
+}
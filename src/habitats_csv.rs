@@ -0,0 +1,115 @@
+use csv::StringRecord;
+use std::error::Error;
+use std::fs::File;
+
+use crate::animal_structs::Habitat;
+use crate::custom_writers_and_readers::*;
+use crate::transaction::Transaction;
+use crate::HABITATS_FILE_PATH;
+
+pub const HABITATS_HEADERS: [&str; 4] = ["name", "biome", "aquatic", "capacity"];
+
+impl Habitat {
+    pub fn to_csv(&self) -> [String; 4] {
+        [
+            self.name.clone(),
+            self.biome.clone(),
+            self.aquatic.to_string(),
+            self.capacity.to_string(),
+        ]
+    }
+
+    pub fn from_csv(record: &StringRecord) -> Result<Habitat, Box<dyn Error>> {
+        Ok(Habitat {
+            name: record[0].to_string(),
+            biome: record[1].to_string(),
+            aquatic: record[2].parse()?,
+            capacity: record[3].parse()?,
+        })
+    }
+}
+
+impl<W: Durable> CustomWriter<W> {
+    pub fn write_habitat(&mut self, habitat: &Habitat) -> csv::Result<()> {
+        self.inner.write_record(habitat.to_csv())?;
+        self.flush()?;
+        Ok(())
+    }
+}
+
+pub trait HabitatReader {
+    fn read_habitat(&mut self, habitat_name: &str) -> Result<Option<Habitat>, Box<dyn Error>>;
+    fn read_habitats(&mut self) -> Result<Vec<Habitat>, Box<dyn Error>>;
+}
+
+impl<R: std::io::Read + std::io::Seek> HabitatReader for CustomReader<R> {
+    fn read_habitat(&mut self, habitat_name: &str) -> Result<Option<Habitat>, Box<dyn Error>> {
+        for result in self.inner.records() {
+            let record = result?;
+            if &record[0] == habitat_name {
+                self.seek_to_beginning()?;
+                return Ok(Some(Habitat::from_csv(&record)?));
+            }
+        }
+        self.seek_to_beginning()?;
+        Ok(None)
+    }
+    fn read_habitats(&mut self) -> Result<Vec<Habitat>, Box<dyn Error>> {
+        let mut habitats = Vec::new();
+        for result in self.inner.records() {
+            habitats.push(Habitat::from_csv(&result?)?);
+        }
+        self.seek_to_beginning()?;
+        Ok(habitats)
+    }
+}
+
+pub fn append_habitat(
+    transaction: &mut Transaction,
+    habitat: &Habitat,
+) -> Result<(), Box<dyn Error>> {
+    transaction.append(HABITATS_FILE_PATH, &habitat.to_csv())
+}
+
+pub fn writer_habitats() -> Result<CustomWriter<File>, Box<dyn Error>> {
+    create_writer_append_for_path(HABITATS_FILE_PATH)
+}
+
+pub fn reader_habitats() -> Result<CustomReader<File>, Box<dyn Error>> {
+    create_reader_for_path(HABITATS_FILE_PATH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::csv_files_creator::create_test_habitats;
+
+    #[test]
+    fn test_read_habitats() -> Result<(), Box<dyn Error>> {
+        create_test_habitats()?;
+
+        let pond = Habitat {
+            name: "pond".to_string(),
+            biome: "wetland".to_string(),
+            aquatic: true,
+            capacity: 30,
+        };
+        let savanna = Habitat {
+            name: "savanna".to_string(),
+            biome: "grassland".to_string(),
+            aquatic: false,
+            capacity: 5,
+        };
+        let mut writer = writer_for_test()?;
+        writer.write_habitat(&pond)?;
+        writer.write_habitat(&savanna)?;
+
+        let mut reader = reader_for_test()?;
+
+        assert_eq!(reader.read_habitat("savanna")?, Some(savanna));
+        assert_eq!(reader.read_habitat("desert")?, None);
+        assert_eq!(reader.read_habitats()?.len(), 2);
+        Ok(())
+    }
+}
@@ -1,42 +1,235 @@
+use rand::thread_rng;
 use std::error::Error;
 use text_io::read;
 
-use crate::animal_structs::{AnimalData, Sex};
+use crate::animal_data_csv::{pivotal_temperature_from_csv, seasons_from_csv, traits_from_csv};
+use crate::animal_structs::{AnimalData, Habitat, Sex};
 use crate::clear_console::clear_terminal_screen;
+use crate::clock;
 use crate::commands::*;
 use crate::custom_string_methods::*;
+use crate::file_lock::{report_busy, FileLock, LockMode};
 use crate::input_validators::*;
+use crate::transaction::take_commit_warnings;
+use crate::undo::begin_operation;
 
 pub fn handle_inputs() -> Result<(), Box<dyn Error>> {
     loop {
         println!("Insert a command, type help to see commands available or exit to... exit");
         let command: String = read!();
         clear_terminal_screen();
-        let command_output = match command.as_str() {
-            "create" => create_animal(get_inputs_create_animal()?)?,
-            "delete" => {
-                let animal_name = get_animal_name_from_input()?;
-                if confirm_deletion(&animal_name) {
-                    delete_animal(animal_name)?
-                } else {
-                    "Deletion aborted".to_string()
-                }
-            }
-            "read" => read_animal(get_animal_name_from_input()?)?,
-            "spawn" => spawn_animal(get_animal_name_from_input()?, get_animal_sex_from_input()?)?,
-            "reproduce" => reproduce_animal(get_animal_name_from_input()?)?,
-            "kill" => kill_animal(get_animal_name_from_input()?)?,
-            "feed" => feed_animal(get_animal_name_from_input()?)?,
-            "count" => count_animal(get_animal_name_from_input()?)?,
-            "help" => help(),
-            "exit" => break,
-            _ => "Invalid command".to_string(),
-        };
-        println!("{}", command_output);
+        if command == "exit" {
+            break;
+        }
+        println!("{}", report_busy(handle_command(&command))?);
     }
     Ok(())
 }
 
+/// Command to run once its inputs have all been asked.
+type PendingCommand = Box<dyn FnOnce() -> Result<String, Box<dyn Error>>>;
+
+fn handle_command(command: &str) -> Result<String, Box<dyn Error>> {
+    let pending_command = ask_command_inputs(command)?;
+    // Every command changes the ecosystem, time passing first, so it holds
+    // the exclusive lock from start to end, though only once the user has
+    // answered, other terminals never waiting on their typing.
+    let _lock = FileLock::acquire(LockMode::Exclusive)?;
+    // Time passes as part of the command, though only the command's own
+    // changes can be undone.
+    begin_operation()?;
+    for event in pass_time()? {
+        println!("{}", event);
+    }
+    let command_output = pending_command()?;
+    end_operation()?;
+    Ok(with_commit_warnings(command_output))
+}
+
+/// Adds to a command's output the warnings left by its commits.
+fn with_commit_warnings(output: String) -> String {
+    let mut lines = vec![output];
+    lines.extend(take_commit_warnings());
+    lines.join("\n")
+}
+
+fn ask_command_inputs(command: &str) -> Result<PendingCommand, Box<dyn Error>> {
+    let pending_command: PendingCommand = match command {
+        "create" => {
+            let animal_data = get_inputs_create_animal()?;
+            Box::new(move || create_animal(animal_data))
+        }
+        "delete" => {
+            let animal_name = get_animal_name_from_input()?;
+            if confirm_deletion(&animal_name) {
+                Box::new(move || delete_animal(animal_name))
+            } else {
+                Box::new(|| Ok("Deletion aborted".to_string()))
+            }
+        }
+        "read" => match get_animal_name_and_time_from_input()? {
+            (animal_name, Some(time)) => Box::new(move || read_animal_at(animal_name, time)),
+            (animal_name, None) => Box::new(move || read_animal(animal_name)),
+        },
+        "spawn" => {
+            let animal_name = get_animal_name_from_input()?;
+            let sex = get_animal_sex_from_input()?;
+            let habitat_name = get_habitat_name_from_input()?;
+            Box::new(move || spawn_animal(animal_name, sex, habitat_name))
+        }
+        "reproduce" => {
+            let animal_name = get_animal_name_from_input()?;
+            Box::new(move || reproduce_animal(animal_name))
+        }
+        "breed" => {
+            let mother_id = get_animal_id_from_input_labelled("Insert the mother id, like 3")?;
+            let father_id = get_animal_id_from_input_labelled("Insert the father id, like 4")?;
+            Box::new(move || breed_animals(mother_id, father_id))
+        }
+        "kill" => {
+            let animal_name = get_animal_name_from_input()?;
+            Box::new(move || kill_animal(animal_name))
+        }
+        "feed" => {
+            let animal_name = get_animal_name_from_input()?;
+            Box::new(move || feed_animal(animal_name))
+        }
+        "count" => match get_animal_name_and_time_from_input()? {
+            (animal_name, Some(time)) => Box::new(move || count_animal_at(animal_name, time)),
+            (animal_name, None) => Box::new(move || count_animal(animal_name)),
+        },
+        "hungry" => Box::new(hungry_animals),
+        "inspect" => {
+            let id = get_animal_id_from_input()?;
+            Box::new(move || inspect_animal(id))
+        }
+        "advance-time" => {
+            let duration = get_duration_from_input()?;
+            Box::new(move || advance_time(duration))
+        }
+        "pedigree" => {
+            let id = get_animal_id_from_input()?;
+            Box::new(move || pedigree(id))
+        }
+        "export-studbook" => {
+            let animal_name = get_animal_name_from_input()?;
+            Box::new(move || export_animal_studbook(animal_name))
+        }
+        "kinship" => {
+            let animal_name = get_animal_name_from_input()?;
+            Box::new(move || kinship(animal_name))
+        }
+        "recommend-pairs" => {
+            let animal_name = get_animal_name_from_input()?;
+            Box::new(move || recommend_pairs(animal_name))
+        }
+        "alleles" => {
+            let animal_name = get_animal_name_from_input()?;
+            Box::new(move || allele_report(animal_name))
+        }
+        "season" => Box::new(season),
+        "set-climate" => {
+            let climate = get_valid_user_input(
+                "Insert the mean temperature in celsius and its seasonal swing, like '20:8'",
+                &valid_climate,
+            );
+            Box::new(move || set_climate(&climate))
+        }
+        "sex-ratios" => {
+            let animal_name = get_animal_name_from_input()?;
+            Box::new(move || sex_ratios(animal_name))
+        }
+        "create-habitat" => {
+            let habitat = get_inputs_create_habitat()?;
+            Box::new(move || create_habitat(habitat))
+        }
+        "habitats" => Box::new(habitats),
+        "check-enclosures" => Box::new(check_enclosures),
+        "set-cohabitation" => {
+            let mode = get_valid_user_input(
+                "Type 'strict' to refuse placing predators with their preys or 'warn' to only warn",
+                &valid_cohabitation_mode,
+            );
+            Box::new(move || set_cohabitation(&mode))
+        }
+        "move" => {
+            let id = get_animal_id_from_input()?;
+            let habitat_name = get_habitat_name_from_input()?;
+            let reason = get_valid_user_input(
+                "Insert the reason of the move, like quarantine or more-space",
+                &valid_animal_name,
+            );
+            Box::new(move || move_animal(id, habitat_name, reason))
+        }
+        "locations" => {
+            let id = get_animal_id_from_input()?;
+            Box::new(move || location_history(id))
+        }
+        "simulate" => Box::new(simulate),
+        "capacity" => Box::new(capacity_report),
+        "infect" => {
+            let animal_name = get_animal_name_from_input()?;
+            let spreads = get_valid_user_input(
+                "Type 'y' if the disease spreads to predators and preys too or 'n' if not",
+                &valid_yes_no,
+            ) == "y";
+            Box::new(move || infect(animal_name, spreads))
+        }
+        "outbreak" => Box::new(outbreak_report),
+        "event" => match get_valid_user_input(
+            "Type 'roll' to roll an environmental event, 'log' to list past ones or 'table' to show the odds",
+            &valid_event_action,
+        )
+        .as_str()
+        {
+            "roll" => Box::new(roll_environmental_event),
+            "log" => Box::new(environmental_events_log),
+            _ => Box::new(environmental_events_table),
+        },
+        "history" => {
+            let animal_name = get_valid_user_input(
+                "Insert animal name, like snake or polar-bear, or 'all'",
+                &valid_animal_name,
+            );
+            let outcome = get_valid_user_input(
+                "Insert an outcome, like born, died, fed, moved, created or deleted, or 'all'",
+                &valid_animal_name,
+            );
+            let dates = get_valid_user_input(
+                "Insert dates like 2026-03-01..2026-04-01, with either end left out, or 'all'",
+                &valid_date_range,
+            );
+            Box::new(move || history(animal_name, outcome, dates))
+        }
+        "replay" => {
+            let date = get_valid_user_input(
+                "Insert a date to replay the journal up to, like 2026-03-01, or 'all'",
+                &valid_date_or_all,
+            );
+            Box::new(move || replay(date))
+        }
+        "undo" => {
+            let count = get_count_from_input("undo")?;
+            Box::new(move || undo(count))
+        }
+        "redo" => {
+            let count = get_count_from_input("redo")?;
+            Box::new(move || redo(count))
+        }
+        "set-lock-timeout" => {
+            let seconds = get_valid_user_input(
+                "Insert how many seconds to wait for another terminal, like 5",
+                &valid_seconds,
+            )
+            .parse()?;
+            Box::new(move || set_lock_timeout(seconds))
+        }
+        "help" => Box::new(|| Ok(help())),
+        _ => Box::new(|| Ok("Invalid command".to_string())),
+    };
+    Ok(pending_command)
+}
+
 pub fn get_valid_user_input(label: &str, validator: &dyn Fn(&String) -> bool) -> String {
     loop {
         println!("{}", label);
@@ -50,6 +243,76 @@ pub fn get_valid_user_input(label: &str, validator: &dyn Fn(&String) -> bool) ->
     }
 }
 
+/// Runs a command given on the command line, only undo, redo, count and
+/// read being available outside the console.
+pub fn handle_arguments(arguments: &[String]) -> Result<String, Box<dyn Error>> {
+    // `--at time` can go anywhere after the command.
+    let mut time = None;
+    let mut rest = Vec::new();
+    let mut arguments = arguments.iter();
+    let command = arguments.next().ok_or("Missing command")?;
+    while let Some(argument) = arguments.next() {
+        if argument == "--at" {
+            match arguments.next() {
+                Some(argument) if valid_time(argument) => time = Some(argument.clone()),
+                _ => return Ok("Invalid time".to_string()),
+            }
+        } else {
+            rest.push(argument.clone());
+        }
+    }
+    // Even reads record an operation, so take the exclusive lock up front.
+    let _lock = FileLock::acquire(LockMode::Exclusive)?;
+    begin_operation()?;
+    let output = match (command.as_str(), rest.as_slice(), time) {
+        ("undo", [], None) => undo(1),
+        ("undo", [count], None) if valid_positive_number(count) => undo(count.parse()?),
+        ("redo", [], None) => redo(1),
+        ("redo", [count], None) if valid_positive_number(count) => redo(count.parse()?),
+        ("count", [animal_name], Some(time)) => count_animal_at(animal_name.clone(), time),
+        ("count", [animal_name], None) => count_animal(animal_name.clone()),
+        ("read", [animal_name], Some(time)) => read_animal_at(animal_name.clone(), time),
+        ("read", [animal_name], None) => read_animal(animal_name.clone()),
+        ("set-lock-timeout", [seconds], None) if valid_seconds(seconds) => {
+            set_lock_timeout(seconds.parse()?)
+        }
+        _ => Ok(
+            "Invalid command, only undo, redo, count, read and set-lock-timeout can be run without the console"
+                .to_string(),
+        ),
+    }?;
+    Ok(with_commit_warnings(output))
+}
+
+/// Animal name, after an optional `--at time` asking for the past, which
+/// can't go back further than the journal.
+fn get_animal_name_and_time_from_input() -> Result<(String, Option<String>), Box<dyn Error>> {
+    let input = get_valid_user_input(
+        "Insert animal name, like snake or polar-bear, after '--at 2026-03-01T09:30' to look back in time",
+        &|input| input == "--at" || valid_animal_name(input),
+    );
+    if input != "--at" {
+        return Ok((input, None));
+    }
+    let Some(start) = journal_start()? else {
+        println!("The journal is empty, so only the present can be looked at");
+        return Ok((get_animal_name_from_input()?, None));
+    };
+    let label = format!(
+        "Insert a time from {} on, like 2026-03-01 or 2026-03-01T09:30",
+        clock::format_time(start)
+    );
+    let time = get_valid_user_input(&label, &|input| {
+        clock::parse_time(input).is_some_and(|at| at >= start)
+    });
+    Ok((get_animal_name_from_input()?, Some(time)))
+}
+
+fn get_count_from_input(action: &str) -> Result<usize, Box<dyn Error>> {
+    let label = format!("Insert how many operations to {}, like 1 or 3", action);
+    Ok(get_valid_user_input(&label, &valid_positive_number).parse()?)
+}
+
 fn get_animal_name_from_input() -> Result<String, Box<dyn Error>> {
     Ok(get_valid_user_input(
         "Insert animal name, like snake or polar-bear",
@@ -57,6 +320,62 @@ fn get_animal_name_from_input() -> Result<String, Box<dyn Error>> {
     ))
 }
 
+fn simulate() -> Result<String, Box<dyn Error>> {
+    let size = get_valid_user_input("Insert the grid size, like 30x15", &valid_grid_size);
+    let (width, height) = size.split_once('x').ok_or("Invalid grid size")?;
+    let mut grid = simulation_grid(width.parse()?, height.parse()?)?;
+    let mut rng = thread_rng();
+    println!("{}", grid.render());
+    loop {
+        let ticks = get_valid_user_input(
+            "Insert how many ticks to run, like 1 or 10, or 'q' to stop",
+            &valid_ticks,
+        );
+        if ticks == "q" {
+            break;
+        }
+        for _ in 0..ticks.parse::<u32>()? {
+            for event in grid.step(&mut rng) {
+                println!("{}", event);
+            }
+        }
+        println!("{}", grid.render());
+    }
+    Ok(format!(
+        "Simulation stopped after {} ticks, animals alive were left as they were",
+        grid.tick()
+    ))
+}
+
+fn get_habitat_name_from_input() -> Result<String, Box<dyn Error>> {
+    Ok(get_valid_user_input(
+        "Insert habitat name, like savanna or coral-reef",
+        &valid_animal_name,
+    ))
+}
+
+fn get_inputs_create_habitat() -> Result<Habitat, Box<dyn Error>> {
+    let name = get_habitat_name_from_input()?;
+    let biome = get_valid_user_input(
+        "Insert habitat biome, like desert, forest, grassland, tundra, wetland or reef",
+        &valid_animal_name,
+    );
+    let aquatic = get_valid_user_input(
+        "Type 'y' if the habitat is aquatic or 'n' if it's on land",
+        &valid_yes_no,
+    );
+    let capacity = get_valid_user_input(
+        "Insert how many animals the habitat can hold",
+        &valid_positive_number,
+    );
+    Ok(Habitat {
+        name,
+        biome,
+        aquatic: aquatic == "y",
+        capacity: capacity.parse()?,
+    })
+}
+
 fn get_inputs_create_animal() -> Result<AnimalData, Box<dyn Error>> {
     let animal_name = get_valid_user_input(
         "Insert animal name, like snake or polar-bear",
@@ -74,12 +393,83 @@ fn get_inputs_create_animal() -> Result<AnimalData, Box<dyn Error>> {
         "Insert animal preys, separated by a '-'. Example: 'cow-snake-shark'",
         &valid_preys,
     );
-    let animal = AnimalData::new(
+    let animal_lifespan =
+        get_valid_user_input("Insert animal lifespan, like 15y or 40d", &valid_lifespan);
+    let animal_maturity = get_valid_user_input(
+        "Insert animal age of maturity, like 2y or 90d",
+        &valid_lifespan,
+    );
+    let animal_breeding_cooldown = get_valid_user_input(
+        "Insert animal rest between breedings, like 1y or 30d",
+        &valid_lifespan,
+    );
+    let animal_youth = get_valid_user_input(
+        "Insert how long the young take to become adults, like 6w or 180d",
+        &valid_lifespan,
+    );
+    let lays_eggs = animal_class.to_class()?.lays_eggs();
+    let (animal_incubation, animal_clutch_size) = if lays_eggs {
+        (
+            get_valid_user_input("Insert eggs incubation, like 3w or 60d", &valid_lifespan),
+            get_valid_user_input(
+                "Insert how many eggs are laid at once",
+                &valid_positive_number,
+            ),
+        )
+    } else {
+        ("none".to_string(), "1".to_string())
+    };
+    let animal_breeding_seasons = get_valid_user_input(
+        "Insert the seasons animals breed in, separated by a '-'. Example: 'spring-summer', or 'all'",
+        &valid_seasons,
+    );
+    let animal_carrying_capacity = get_valid_user_input(
+        "Insert how many animals of the kind can live before births slow down to none, like 50, or 'none'",
+        &valid_carrying_capacity,
+    );
+    let animal_traits = get_valid_user_input(
+        "Insert heritable traits as 'trait:dominant>recessive' or 'trait:allele=allele' for codominant ones, separated by a ';'. Example: 'color:green>albino;size:large=small', or 'none'",
+        &valid_traits,
+    );
+    let animal_reproduction = get_valid_user_input(
+        "Insert animal reproduction mode (sexual, asexual, hermaphrodite, protandrous or protogynous)",
+        &valid_reproduction_mode,
+    );
+    let animal_pivotal_temperature = if animal_class == "reptile" {
+        get_valid_user_input(
+            "Insert the pivotal temperature as 'celsius:range:warm_sex', like '29.5:2:female', or 'none' if sex does not depend on temperature",
+            &valid_pivotal_temperature,
+        )
+    } else {
+        "none".to_string()
+    };
+    let mut animal = AnimalData::new(
         animal_name,
         animal_class.to_class()?,
         animal_predators.split("-").map(|s| s.to_string()).collect(),
         animal_preys.split("-").map(|s| s.to_string()).collect(),
     );
+    animal.lifespan_days = animal_lifespan.to_days()?;
+    animal.maturity_days = animal_maturity.to_days()?;
+    animal.breeding_cooldown_days = animal_breeding_cooldown.to_days()?;
+    animal.juvenile_days = animal_youth.to_days()?;
+    if lays_eggs {
+        animal.incubation_days = animal_incubation.to_days()?;
+    }
+    animal.clutch_size = animal_clutch_size.parse()?;
+    if animal_carrying_capacity != "none" {
+        animal.carrying_capacity = Some(animal_carrying_capacity.parse()?);
+    }
+    if animal_breeding_seasons != "all" {
+        animal.breeding_seasons = seasons_from_csv(&animal_breeding_seasons)?;
+    }
+    animal.reproduction = animal_reproduction.to_reproduction_mode()?;
+    if animal_pivotal_temperature != "none" {
+        animal.pivotal_temperature = pivotal_temperature_from_csv(&animal_pivotal_temperature)?;
+    }
+    if animal_traits != "none" {
+        animal.traits = traits_from_csv(&animal_traits)?;
+    }
     Ok(animal)
 }
 
@@ -88,11 +478,7 @@ fn confirm_deletion(animal_name: &String) -> bool {
         "Are you sure to delete {} data? Insert 'y' to confirm or any other character to return",
         animal_name
     );
-    if get_valid_user_input(label.as_str(), &any_input_is_valid) == "y" {
-        true
-    } else {
-        false
-    }
+    get_valid_user_input(label.as_str(), &any_input_is_valid) == "y"
 }
 
 fn get_animal_sex_from_input() -> Result<Sex, Box<dyn Error>> {
@@ -102,3 +488,15 @@ fn get_animal_sex_from_input() -> Result<Sex, Box<dyn Error>> {
         _ => Err("Invalid sex".into()),
     }
 }
+
+fn get_animal_id_from_input() -> Result<u32, Box<dyn Error>> {
+    get_animal_id_from_input_labelled("Insert animal id, like 3")
+}
+
+fn get_animal_id_from_input_labelled(label: &str) -> Result<u32, Box<dyn Error>> {
+    Ok(get_valid_user_input(label, &valid_id).parse()?)
+}
+
+fn get_duration_from_input() -> Result<u64, Box<dyn Error>> {
+    get_valid_user_input("Insert a duration, like 12h, 3d, 2w or 1y", &valid_duration).to_seconds()
+}
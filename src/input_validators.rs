@@ -1,5 +1,11 @@
+// Validators take the `&String` that `get_valid_user_input` reads.
+#![allow(clippy::ptr_arg)]
+
 use regex::Regex;
 
+use crate::clock::{parse_date, parse_time};
+use crate::custom_string_methods::CustomStringMethods;
+
 fn valid_string_lowercased_with_hyphens(input: &String) -> bool {
     let pattern = Regex::new(r"^[a-z]+(?:-[a-z]+)*$").unwrap();
     pattern.is_match(input)
@@ -17,6 +23,17 @@ pub fn valid_class(input: &String) -> bool {
     .contains(&input.as_str())
 }
 
+pub fn valid_reproduction_mode(input: &String) -> bool {
+    [
+        "sexual",
+        "asexual",
+        "hermaphrodite",
+        "protandrous",
+        "protogynous",
+    ]
+    .contains(&input.as_str())
+}
+
 pub fn valid_animal_name(input: &String) -> bool {
     valid_string_lowercased_with_hyphens(input)
 }
@@ -36,6 +53,100 @@ pub fn valid_sex(input: &String) -> bool {
     ["m", "f"].contains(&input.as_str())
 }
 
+pub fn valid_cohabitation_mode(input: &String) -> bool {
+    input == "strict" || input == "warn"
+}
+
+pub fn valid_grid_size(input: &String) -> bool {
+    let pattern = Regex::new(r"^[1-9][0-9]{0,2}x[1-9][0-9]{0,2}$").unwrap();
+    pattern.is_match(input)
+}
+
+pub fn valid_carrying_capacity(input: &String) -> bool {
+    input == "none" || valid_positive_number(input)
+}
+
+pub fn valid_ticks(input: &String) -> bool {
+    input == "q" || valid_positive_number(input)
+}
+
+pub fn valid_date(input: &String) -> bool {
+    parse_date(input).is_some()
+}
+
+pub fn valid_time(input: &String) -> bool {
+    parse_time(input).is_some()
+}
+
+pub fn valid_date_or_all(input: &String) -> bool {
+    input == "all" || valid_date(input)
+}
+
+pub fn valid_date_range(input: &String) -> bool {
+    if input == "all" {
+        return true;
+    }
+    let pattern = Regex::new(r"^(\d{4}-\d{2}-\d{2})?\.\.(\d{4}-\d{2}-\d{2})?$").unwrap();
+    pattern.is_match(input)
+        && input
+            .split("..")
+            .filter(|date| !date.is_empty())
+            .all(|date| parse_date(date).is_some())
+}
+
+pub fn valid_event_action(input: &String) -> bool {
+    ["roll", "log", "table"].contains(&input.as_str())
+}
+
+pub fn valid_yes_no(input: &String) -> bool {
+    input == "y" || input == "n"
+}
+
+pub fn valid_seconds(input: &String) -> bool {
+    input.parse::<u64>().is_ok()
+}
+
+pub fn valid_id(input: &String) -> bool {
+    input.parse::<u32>().is_ok()
+}
+
+pub fn valid_duration(input: &String) -> bool {
+    let pattern = Regex::new(r"^[1-9][0-9]*[hdwy]$").unwrap();
+    pattern.is_match(input) && input.to_seconds().is_ok()
+}
+
+pub fn valid_traits(input: &String) -> bool {
+    let pattern = Regex::new(r"^[a-z]+:[a-z]+[>=][a-z]+(?:;[a-z]+:[a-z]+[>=][a-z]+)*$").unwrap();
+    input == "none" || pattern.is_match(input)
+}
+
+pub fn valid_pivotal_temperature(input: &String) -> bool {
+    let pattern =
+        Regex::new(r"^[0-9]+(?:\.[0-9]+)?:[0-9]*[1-9][0-9]*(?:\.[0-9]+)?:(?:male|female)$")
+            .unwrap();
+    input == "none" || pattern.is_match(input)
+}
+
+pub fn valid_lifespan(input: &String) -> bool {
+    valid_duration(input) && !input.ends_with('h') && input.to_days().is_ok()
+}
+
+pub fn valid_seasons(input: &String) -> bool {
+    let pattern =
+        Regex::new(r"^(?:spring|summer|autumn|winter)(?:-(?:spring|summer|autumn|winter))*$")
+            .unwrap();
+    input == "all" || pattern.is_match(input)
+}
+
+pub fn valid_climate(input: &String) -> bool {
+    let pattern = Regex::new(r"^-?[0-9]+(?:\.[0-9]+)?:[0-9]+(?:\.[0-9]+)?$").unwrap();
+    pattern.is_match(input)
+}
+
+pub fn valid_positive_number(input: &String) -> bool {
+    input.parse::<u32>().is_ok_and(|size| size > 0)
+}
+
 #[cfg(test)]
 mod tests {
     use std::error::Error;
@@ -87,4 +198,94 @@ mod tests {
         assert!(!valid_sex(&"male".to_string()));
         Ok(())
     }
+
+    #[test]
+    fn test_valid_duration() -> Result<(), Box<dyn Error>> {
+        assert!(valid_duration(&"12h".to_string()));
+        assert!(valid_duration(&"3d".to_string()));
+        assert!(valid_duration(&"10y".to_string()));
+
+        assert!(!valid_duration(&"0d".to_string()));
+        assert!(!valid_duration(&"3".to_string()));
+        assert!(!valid_duration(&"d".to_string()));
+        assert!(!valid_duration(&"3 d".to_string()));
+        assert!(!valid_duration(&"999999999999y".to_string()));
+
+        assert!(valid_lifespan(&"40d".to_string()));
+        assert!(!valid_lifespan(&"40h".to_string()));
+        assert!(!valid_lifespan(&"99999999y".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_valid_pivotal_temperature() -> Result<(), Box<dyn Error>> {
+        assert!(valid_pivotal_temperature(&"none".to_string()));
+        assert!(valid_pivotal_temperature(&"29.5:2:female".to_string()));
+        assert!(valid_pivotal_temperature(&"31:1.5:male".to_string()));
+
+        assert!(!valid_pivotal_temperature(&"29.5:0:female".to_string()));
+        assert!(!valid_pivotal_temperature(
+            &"29.5:2:hermaphrodite".to_string()
+        ));
+        assert!(!valid_pivotal_temperature(&"29.5:female".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_valid_traits() -> Result<(), Box<dyn Error>> {
+        assert!(valid_traits(&"none".to_string()));
+        assert!(valid_traits(&"color:green>albino".to_string()));
+        assert!(valid_traits(
+            &"color:green>albino;size:large=small".to_string()
+        ));
+
+        assert!(!valid_traits(&"color:green".to_string()));
+        assert!(!valid_traits(&"color:green>albino;".to_string()));
+        assert!(!valid_traits(&"color:green-albino".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_valid_seasons() -> Result<(), Box<dyn Error>> {
+        assert!(valid_seasons(&"all".to_string()));
+        assert!(valid_seasons(&"spring".to_string()));
+        assert!(valid_seasons(&"spring-summer".to_string()));
+
+        assert!(!valid_seasons(&"spring-".to_string()));
+        assert!(!valid_seasons(&"fall".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_valid_climate() -> Result<(), Box<dyn Error>> {
+        assert!(valid_climate(&"20:8".to_string()));
+        assert!(valid_climate(&"-2.5:12.5".to_string()));
+
+        assert!(!valid_climate(&"20".to_string()));
+        assert!(!valid_climate(&"20:-8".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_valid_grid_size() -> Result<(), Box<dyn Error>> {
+        assert!(valid_grid_size(&"30x15".to_string()));
+        assert!(valid_grid_size(&"1x1".to_string()));
+
+        assert!(!valid_grid_size(&"0x15".to_string()));
+        assert!(!valid_grid_size(&"30".to_string()));
+        assert!(!valid_grid_size(&"1000x10".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_valid_date_range() -> Result<(), Box<dyn Error>> {
+        assert!(valid_date_range(&"all".to_string()));
+        assert!(valid_date_range(&"2026-03-01..2026-04-01".to_string()));
+        assert!(valid_date_range(&"2026-03-01..".to_string()));
+        assert!(valid_date_range(&"..2026-04-01".to_string()));
+
+        assert!(!valid_date_range(&"2026-03-01".to_string()));
+        assert!(!valid_date_range(&"2026-13-01..".to_string()));
+        Ok(())
+    }
 }
@@ -0,0 +1,307 @@
+use csv::StringRecord;
+use std::error::Error;
+use std::fs::File;
+
+use crate::animal_csv_shared_functions::{optional_from_csv, optional_to_csv};
+use crate::animal_structs::{AnimalAlive, AnimalData, JournalEntry};
+use crate::custom_writers_and_readers::*;
+use crate::transaction::Transaction;
+use crate::JOURNAL_FILE_PATH;
+
+pub const JOURNAL_HEADERS: [&str; 9] = [
+    "recorded_at",
+    "operation",
+    "command",
+    "species",
+    "individual",
+    "outcome",
+    "cause",
+    "before",
+    "after",
+];
+
+/// Rows are nested as a single CSV line, empty when there was no row.
+pub fn row_to_csv(row: &Option<Vec<String>>) -> Result<String, Box<dyn Error>> {
+    let Some(row) = row else {
+        return Ok(String::new());
+    };
+    let mut writer = csv::WriterBuilder::new()
+        .terminator(csv::Terminator::Any(b'\n'))
+        .from_writer(Vec::new());
+    writer.write_record(row)?;
+    let line = String::from_utf8(writer.into_inner()?)?;
+    Ok(line.trim_end_matches('\n').to_string())
+}
+
+pub fn row_from_csv(field: &str) -> Result<Option<Vec<String>>, Box<dyn Error>> {
+    if field.is_empty() {
+        return Ok(None);
+    }
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(field.as_bytes());
+    let record = reader.records().next().ok_or("Invalid journal row")??;
+    Ok(Some(record.iter().map(|field| field.to_string()).collect()))
+}
+
+impl JournalEntry {
+    pub fn to_csv(&self) -> Result<[String; 9], Box<dyn Error>> {
+        Ok([
+            self.recorded_at.to_string(),
+            self.operation.to_string(),
+            self.command.clone(),
+            self.species.clone(),
+            optional_to_csv(self.individual),
+            self.outcome.clone(),
+            self.cause.clone(),
+            row_to_csv(&self.before)?,
+            row_to_csv(&self.after)?,
+        ])
+    }
+
+    pub fn from_csv(record: &StringRecord) -> Result<JournalEntry, Box<dyn Error>> {
+        Ok(JournalEntry {
+            recorded_at: record[0].parse()?,
+            operation: record[1].parse()?,
+            command: record[2].to_string(),
+            species: record[3].to_string(),
+            individual: optional_from_csv(&record[4])?,
+            outcome: record[5].to_string(),
+            cause: record[6].to_string(),
+            before: row_from_csv(&record[7])?,
+            after: row_from_csv(&record[8])?,
+        })
+    }
+}
+
+/// Entries recording the species and animals alive a journal starts from,
+/// as operation 0, so that replaying it accounts for them.
+pub fn snapshot_entries(
+    species: &[AnimalData],
+    animals: &[AnimalAlive],
+    recorded_at: u64,
+) -> Vec<JournalEntry> {
+    let entry = |species: String, individual, row: Vec<String>| JournalEntry {
+        recorded_at,
+        operation: 0,
+        command: "snapshot".to_string(),
+        species,
+        individual,
+        outcome: "existed".to_string(),
+        cause: String::new(),
+        before: None,
+        after: Some(row),
+    };
+    let species = species
+        .iter()
+        .map(|animal_data| entry(animal_data.name(), None, animal_data.to_csv().to_vec()));
+    let animals = animals
+        .iter()
+        .map(|animal| entry(animal.name(), Some(animal.id()), animal.to_csv().to_vec()));
+    species.chain(animals).collect()
+}
+
+/// Which journal entries to show, every field left out matching anything.
+/// The time range includes `from` and excludes `to`.
+#[derive(Debug, Default)]
+pub struct JournalQuery {
+    pub operation: Option<u64>,
+    pub species: Option<String>,
+    pub outcome: Option<String>,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+impl JournalQuery {
+    pub fn matches(&self, entry: &JournalEntry) -> bool {
+        self.operation
+            .is_none_or(|operation| operation == entry.operation)
+            && self
+                .species
+                .as_ref()
+                .is_none_or(|species| species == &entry.species)
+            && self
+                .outcome
+                .as_ref()
+                .is_none_or(|outcome| outcome == &entry.outcome)
+            && self.from.is_none_or(|from| entry.recorded_at >= from)
+            && self.to.is_none_or(|to| entry.recorded_at < to)
+    }
+}
+
+impl<W: Durable> CustomWriter<W> {
+    pub fn write_journal_entry(&mut self, entry: &JournalEntry) -> Result<(), Box<dyn Error>> {
+        self.inner.write_record(entry.to_csv()?)?;
+        self.flush()?;
+        Ok(())
+    }
+}
+
+pub trait JournalReader {
+    fn read_journal(&mut self, query: &JournalQuery) -> Result<Vec<JournalEntry>, Box<dyn Error>> {
+        self.read_journal_from(0, query)
+    }
+    /// Entries from byte `start` on, which begins a row unless it is 0.
+    fn read_journal_from(
+        &mut self,
+        start: u64,
+        query: &JournalQuery,
+    ) -> Result<Vec<JournalEntry>, Box<dyn Error>>;
+}
+
+impl<R: std::io::Read + std::io::Seek> JournalReader for CustomReader<R> {
+    fn read_journal_from(
+        &mut self,
+        start: u64,
+        query: &JournalQuery,
+    ) -> Result<Vec<JournalEntry>, Box<dyn Error>> {
+        if start > 0 {
+            let mut position = csv::Position::new();
+            position.set_byte(start);
+            self.inner.seek(position)?;
+        }
+        let mut entries = Vec::new();
+        for result in self.inner.records() {
+            let entry = JournalEntry::from_csv(&result?)?;
+            if query.matches(&entry) {
+                entries.push(entry);
+            }
+        }
+        self.seek_to_beginning()?;
+        Ok(entries)
+    }
+}
+
+/// Stages entries at the end of the journal, to be recorded along with the
+/// changes they describe.
+pub fn append_journal_entries(
+    transaction: &mut Transaction,
+    entries: &[JournalEntry],
+) -> Result<(), Box<dyn Error>> {
+    let rows = entries
+        .iter()
+        .map(|entry| Ok(entry.to_csv()?.to_vec()))
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    transaction.append_all(JOURNAL_FILE_PATH, &rows)
+}
+
+pub fn writer_journal() -> Result<CustomWriter<File>, Box<dyn Error>> {
+    create_writer_append_for_path(JOURNAL_FILE_PATH)
+}
+
+pub fn reader_journal() -> Result<CustomReader<File>, Box<dyn Error>> {
+    create_reader_for_path(JOURNAL_FILE_PATH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::animal_structs::animals_alive_models::*;
+    use crate::animal_structs::animals_data_models::*;
+    use crate::csv_files_creator::create_test_journal;
+    use crate::replay::Projection;
+    use crate::test_path;
+
+    fn entry(recorded_at: u64, species: &str, outcome: &str) -> JournalEntry {
+        JournalEntry {
+            recorded_at,
+            operation: recorded_at / 10,
+            command: "kill".to_string(),
+            species: species.to_string(),
+            individual: Some(3),
+            outcome: outcome.to_string(),
+            cause: "hunted by a snake".to_string(),
+            before: None,
+            after: None,
+        }
+    }
+
+    #[test]
+    fn test_read_journal() -> Result<(), Box<dyn Error>> {
+        create_test_journal()?;
+
+        let mut writer = writer_for_test()?;
+        writer.write_journal_entry(&entry(10, "chameleon", "died"))?;
+        writer.write_journal_entry(&entry(20, "snake", "born"))?;
+        writer.write_journal_entry(&JournalEntry {
+            individual: None,
+            ..entry(30, "snake", "created")
+        })?;
+
+        let mut reader = reader_for_test()?;
+
+        assert_eq!(reader.read_journal(&JournalQuery::default())?.len(), 3);
+        let snakes = JournalQuery {
+            species: Some("snake".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(reader.read_journal(&snakes)?.len(), 2);
+        let deaths = JournalQuery {
+            outcome: Some("died".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            reader.read_journal(&deaths)?,
+            vec![entry(10, "chameleon", "died")]
+        );
+        let range = JournalQuery {
+            from: Some(20),
+            to: Some(30),
+            ..Default::default()
+        };
+        assert_eq!(
+            reader.read_journal(&range)?,
+            vec![entry(20, "snake", "born")]
+        );
+        let operation = JournalQuery {
+            operation: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(reader.read_journal(&operation)?.len(), 1);
+
+        // Read from where the journal ended, only the entries since show.
+        let len = std::fs::metadata(test_path())?.len();
+        writer.write_journal_entry(&entry(40, "snake", "died"))?;
+        let mut reader = reader_for_test()?;
+        assert_eq!(
+            reader.read_journal_from(len, &JournalQuery::default())?,
+            vec![entry(40, "snake", "died")]
+        );
+        assert_eq!(reader.read_journal(&JournalQuery::default())?.len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot_entries() {
+        let species = vec![snake_data()];
+        let animals = vec![snake_female(), snake_male()];
+        let entries = snapshot_entries(&species, &animals, 10);
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|entry| entry.operation == 0));
+
+        let species_rows = vec![snake_data().to_csv().to_vec()];
+        let animal_rows = vec![
+            snake_female().to_csv().to_vec(),
+            snake_male().to_csv().to_vec(),
+        ];
+        let projection = Projection::from_journal(&entries);
+        assert!(projection
+            .divergences(&species_rows, &animal_rows)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_journal_rows() -> Result<(), Box<dyn Error>> {
+        let row = Some(vec![
+            "snake".to_string(),
+            "color:green/albino;size:small/small".to_string(),
+            "a \"quoted\", field".to_string(),
+            String::new(),
+        ]);
+        assert_eq!(row_from_csv(&row_to_csv(&row)?)?, row);
+        assert_eq!(row_to_csv(&None)?, "");
+        assert_eq!(row_from_csv("")?, None);
+        Ok(())
+    }
+}
@@ -3,14 +3,68 @@ pub mod animal_csv_shared_functions;
 pub mod animal_data_csv;
 pub mod animal_structs;
 pub mod clear_console;
+pub mod climate;
+pub mod clock;
+pub mod cohabitation;
 pub mod commands;
 pub mod csv_files_creator;
 pub mod custom_string_methods;
 pub mod custom_writers_and_readers;
+pub mod disease;
+pub mod environmental_events_csv;
+pub mod file_lock;
+pub mod genetics;
+pub mod grid;
+pub mod habitats_csv;
 pub mod input_handler;
 pub mod input_validators;
+pub mod journal_csv;
+pub mod migration;
+pub mod pedigree;
+pub mod quarantine;
+pub mod replay;
+pub mod session_cache;
+pub mod studbook_csv;
 pub mod temp_file_handler;
+pub mod transaction;
+pub mod transfers_csv;
+pub mod undo;
 
 pub const ANIMALS_DATA_FILE_PATH: &str = "animals_data.csv";
 pub const ANIMALS_ALIVE_FILE_PATH: &str = "animals_alive.csv";
-pub const TEST_PATH: &str = "test_file.csv";
+pub const STUDBOOK_FILE_PATH: &str = "studbook.csv";
+pub const HABITATS_FILE_PATH: &str = "habitats.csv";
+pub const TRANSFERS_FILE_PATH: &str = "transfers.csv";
+pub const ENVIRONMENTAL_EVENTS_FILE_PATH: &str = "environmental_events.csv";
+pub const ENVIRONMENTAL_EVENTS_LOG_FILE_PATH: &str = "environmental_events_log.csv";
+pub const JOURNAL_FILE_PATH: &str = "journal.csv";
+pub const REPLAYED_ANIMALS_DATA_FILE_PATH: &str = "replayed_animals_data.csv";
+pub const REPLAYED_ANIMALS_ALIVE_FILE_PATH: &str = "replayed_animals_alive.csv";
+pub const CLOCK_FILE_PATH: &str = "clock.txt";
+pub const CLIMATE_FILE_PATH: &str = "climate.txt";
+pub const COHABITATION_FILE_PATH: &str = "cohabitation.txt";
+pub const DISEASE_FILE_PATH: &str = "disease.txt";
+pub const LOCK_FILE_PATH: &str = "ecosystem.lock";
+pub const LOCK_TIMEOUT_FILE_PATH: &str = "lock_timeout.txt";
+pub const QUARANTINE_FILE_PATH: &str = "quarantine.txt";
+pub const TRANSACTION_FILE_PATH: &str = "transaction.txt";
+pub const UNDO_FILE_PATH: &str = "undo.txt";
+
+/// Temp dir of the current test thread, so tests running together don't
+/// share their files.
+#[cfg(test)]
+pub fn test_dir() -> std::path::PathBuf {
+    thread_local! {
+        static TEST_DIR: tempfile::TempDir = tempfile::tempdir().expect("Failed to create test dir");
+    }
+    TEST_DIR.with(|dir| dir.path().to_path_buf())
+}
+
+/// File the tests read and write, in the test thread's temp dir.
+#[cfg(test)]
+pub fn test_path() -> String {
+    test_dir()
+        .join("test_file.csv")
+        .to_string_lossy()
+        .into_owned()
+}
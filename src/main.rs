@@ -1,12 +1,52 @@
 use animals_cli::clear_console::clear_terminal_screen;
 use animals_cli::csv_files_creator::*;
-use animals_cli::input_handler::handle_inputs;
+use animals_cli::file_lock::{report_busy, FileLock, LockMode};
+use animals_cli::input_handler::{handle_arguments, handle_inputs};
+use animals_cli::migration::migrate_csv_files;
+use animals_cli::quarantine::quarantine_torn_rows;
+use animals_cli::transaction::recover_transaction;
+use animals_cli::QUARANTINE_FILE_PATH;
+use std::env;
 use std::error::Error;
 
 fn main() -> Result<(), Box<dyn Error>> {
-    create_animals_data_if_not_exists()?;
-    create_animals_alive_if_not_exists()?;
-    clear_terminal_screen();
+    let (recovered, quarantined, migrated) = {
+        // Getting the files ready writes to them, so it holds the exclusive
+        // lock throughout rather than upgrading a shared one.
+        let _lock = FileLock::acquire(LockMode::Exclusive)?;
+        let recovered = recover_transaction()?;
+        create_animals_data_if_not_exists()?;
+        create_animals_alive_if_not_exists()?;
+        create_studbook_if_not_exists()?;
+        create_habitats_if_not_exists()?;
+        create_transfers_if_not_exists()?;
+        create_environmental_events_if_not_exists()?;
+        let quarantined = quarantine_torn_rows()?;
+        let migrated = migrate_csv_files()?;
+        // Once up to date, the species and animals alive can be snapshot.
+        create_journal_if_not_exists()?;
+        (recovered, quarantined, migrated)
+    };
+    let arguments = env::args().skip(1).collect::<Vec<_>>();
+    if arguments.is_empty() {
+        clear_terminal_screen();
+    }
+    if recovered {
+        println!("Finished saving changes interrupted last time");
+    }
+    for path in quarantined {
+        println!(
+            "Set aside a partially written row of {} in {}",
+            path, QUARANTINE_FILE_PATH
+        );
+    }
+    for path in migrated {
+        println!("Added the columns missing from {}", path);
+    }
+    if !arguments.is_empty() {
+        println!("{}", report_busy(handle_arguments(&arguments))?);
+        return Ok(());
+    }
     handle_inputs()?;
 
     Ok(())
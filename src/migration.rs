@@ -0,0 +1,138 @@
+use csv::StringRecord;
+use std::error::Error;
+
+use crate::animal_alive_csv::{sex_from_csv, ANIMALS_ALIVE_HEADERS};
+use crate::animal_csv_shared_functions::{field, header_index};
+use crate::animal_data_csv::ANIMALS_DATA_HEADERS;
+use crate::animal_structs::*;
+use crate::custom_string_methods::CustomStringMethods;
+use crate::custom_writers_and_readers::create_reader_for_path;
+use crate::transaction::Transaction;
+use crate::{ANIMALS_ALIVE_FILE_PATH, ANIMALS_DATA_FILE_PATH};
+
+/// Adds the columns missing from files written by older versions, returning
+/// the files it changed. Columns are matched by header, so any older
+/// version can be brought up to date.
+pub fn migrate_csv_files() -> Result<Vec<String>, Box<dyn Error>> {
+    let mut transaction = Transaction::new();
+    let mut migrated = Vec::new();
+
+    let default_animal_data = |headers: &StringRecord, record: &StringRecord| {
+        let column = |header| header_index(headers, header).and_then(|index| field(record, index));
+        let split = |field: &str| field.split('-').map(String::from).collect::<Vec<_>>();
+        let animal_data = AnimalData::new(
+            column("name")?.to_string(),
+            column("class")?.to_string().to_class()?,
+            split(column("predators")?),
+            split(column("preys")?),
+        );
+        Ok(animal_data.to_csv().to_vec())
+    };
+    if migrate_for_path(
+        &mut transaction,
+        ANIMALS_DATA_FILE_PATH,
+        &ANIMALS_DATA_HEADERS,
+        default_animal_data,
+    )? {
+        migrated.push(ANIMALS_DATA_FILE_PATH.to_string());
+    }
+
+    // Files without ids predate the studbook, so numbering from one is safe.
+    let mut next_id = 0;
+    let default_animal_alive = |headers: &StringRecord, record: &StringRecord| {
+        let column = |header| header_index(headers, header).and_then(|index| field(record, index));
+        next_id += 1;
+        let animal = AnimalAlive::born(next_id, column("name")?, sex_from_csv(column("sex")?)?);
+        Ok(animal.to_csv().to_vec())
+    };
+    if migrate_for_path(
+        &mut transaction,
+        ANIMALS_ALIVE_FILE_PATH,
+        &ANIMALS_ALIVE_HEADERS,
+        default_animal_alive,
+    )? {
+        migrated.push(ANIMALS_ALIVE_FILE_PATH.to_string());
+    }
+
+    if !migrated.is_empty() {
+        transaction.commit()?;
+    }
+    Ok(migrated)
+}
+
+/// Stages `path` with `new_headers` if its headers differ, keeping the
+/// fields of the columns it has and taking the others from `defaults`,
+/// which returns a whole row in the new order.
+fn migrate_for_path<F>(
+    transaction: &mut Transaction,
+    path: &str,
+    new_headers: &[&str],
+    mut defaults: F,
+) -> Result<bool, Box<dyn Error>>
+where
+    F: FnMut(&StringRecord, &StringRecord) -> Result<Vec<String>, Box<dyn Error>>,
+{
+    let headers = create_reader_for_path(path)?.inner.headers()?.clone();
+    if headers.iter().eq(new_headers.iter().copied()) {
+        return Ok(false);
+    }
+    transaction.migrate(path, new_headers, |headers, record| {
+        let defaults = defaults(headers, &record)?;
+        new_headers
+            .iter()
+            .zip(defaults)
+            .map(|(header, default)| {
+                match headers.iter().position(|old_header| old_header == *header) {
+                    Some(index) => field(&record, index).map(str::to_string),
+                    None => Ok(default),
+                }
+            })
+            .collect()
+    })?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_migrate_for_path() -> Result<(), Box<dyn Error>> {
+        let dir = tempdir()?;
+        let path = dir.path().join("animals.csv");
+        let path = path.to_str().unwrap();
+        fs::write(path, "name,sex\nsnake,male\nsnake,female\n")?;
+        let defaults = |_: &StringRecord, record: &StringRecord| {
+            Ok(vec![
+                String::new(),
+                "7".to_string(),
+                String::new(),
+                format!("{}s", &record[0]),
+            ])
+        };
+
+        let mut transaction = Transaction::in_dir(dir.path());
+        assert!(migrate_for_path(
+            &mut transaction,
+            path,
+            &["sex", "id", "name", "plural"],
+            defaults
+        )?);
+        transaction.commit()?;
+        assert_eq!(
+            fs::read_to_string(path)?,
+            "sex,id,name,plural\nmale,7,snake,snakes\nfemale,7,snake,snakes\n"
+        );
+
+        let mut transaction = Transaction::in_dir(dir.path());
+        assert!(!migrate_for_path(
+            &mut transaction,
+            path,
+            &["sex", "id", "name", "plural"],
+            defaults
+        )?);
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::animal_structs::StudbookEntry;
+
+/// Labelled relatives of an animal, with the id to keep walking the tree from.
+type Branches = fn(&Pedigree, u32) -> Vec<(String, Option<u32>)>;
+
+/// Family links between every animal registered in the studbook.
+pub struct Pedigree {
+    entries: HashMap<u32, StudbookEntry>,
+    kinships: RefCell<HashMap<(u32, u32), f64>>,
+}
+
+impl Pedigree {
+    pub fn new(entries: Vec<StudbookEntry>) -> Self {
+        Pedigree {
+            entries: entries.into_iter().map(|entry| (entry.id, entry)).collect(),
+            kinships: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, id: u32) -> Option<&StudbookEntry> {
+        self.entries.get(&id)
+    }
+
+    pub fn children(&self, id: u32) -> Vec<&StudbookEntry> {
+        let mut children = self
+            .entries
+            .values()
+            .filter(|entry| entry.mother == Some(id) || entry.father == Some(id))
+            .collect::<Vec<_>>();
+        children.sort_by_key(|entry| entry.id);
+        children
+    }
+
+    /// Probability that an allele picked at random from `a` and one from `b`
+    /// are identical by descent. Animals with unknown parents are treated as
+    /// unrelated founders.
+    pub fn kinship(&self, a: u32, b: u32) -> f64 {
+        // Ids grow with births, so the larger one can't be an ancestor of the other.
+        let (older, younger) = (a.min(b), a.max(b));
+        if let Some(kinship) = self.kinships.borrow().get(&(older, younger)) {
+            return *kinship;
+        }
+        let Some(entry) = self.get(younger) else {
+            return 0.0;
+        };
+        let kinship = if older == younger {
+            0.5 * (1.0 + self.inbreeding(younger))
+        } else {
+            let with_parent =
+                |parent: Option<u32>| parent.map_or(0.0, |parent| self.kinship(older, parent));
+            0.5 * (with_parent(entry.mother) + with_parent(entry.father))
+        };
+        self.kinships.borrow_mut().insert((older, younger), kinship);
+        kinship
+    }
+
+    /// Inbreeding coefficient: the kinship between the parents.
+    pub fn inbreeding(&self, id: u32) -> f64 {
+        match self.get(id).map(|entry| (entry.mother, entry.father)) {
+            Some((Some(mother), Some(father))) => self.kinship(mother, father),
+            _ => 0.0,
+        }
+    }
+
+    /// Average kinship of an animal with every animal of `population`,
+    /// itself included.
+    pub fn mean_kinship(&self, id: u32, population: &[u32]) -> f64 {
+        if population.is_empty() {
+            return 0.0;
+        }
+        let total = population
+            .iter()
+            .map(|other| self.kinship(id, *other))
+            .sum::<f64>();
+        total / population.len() as f64
+    }
+
+    fn parent_branches(&self, id: u32) -> Vec<(String, Option<u32>)> {
+        let Some(entry) = self.get(id) else {
+            return Vec::new();
+        };
+        [("mother", entry.mother), ("father", entry.father)]
+            .into_iter()
+            .map(|(role, parent)| {
+                let label = match parent.and_then(|parent| self.get(parent)) {
+                    Some(parent) => parent.label(),
+                    None => "unknown".to_string(),
+                };
+                (format!("{}: {}", role, label), parent)
+            })
+            .collect()
+    }
+
+    fn child_branches(&self, id: u32) -> Vec<(String, Option<u32>)> {
+        self.children(id)
+            .into_iter()
+            .map(|child| (child.label(), Some(child.id)))
+            .collect()
+    }
+
+    pub fn ancestors_tree(&self, id: u32) -> Option<String> {
+        self.tree(id, Pedigree::parent_branches)
+    }
+
+    pub fn descendants_tree(&self, id: u32) -> Option<String> {
+        self.tree(id, Pedigree::child_branches)
+    }
+
+    fn tree(&self, id: u32, branches: Branches) -> Option<String> {
+        let mut lines = vec![self.get(id)?.label()];
+        self.draw_branches(id, "", branches, &mut lines);
+        Some(lines.join("\n"))
+    }
+
+    fn draw_branches(&self, id: u32, prefix: &str, branches: Branches, lines: &mut Vec<String>) {
+        let nodes = branches(self, id);
+        let last_index = nodes.len().saturating_sub(1);
+        for (index, (label, next_id)) in nodes.into_iter().enumerate() {
+            let (connector, indent) = if index == last_index {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            lines.push(format!("{}{}{}", prefix, connector, label));
+            if let Some(next_id) = next_id {
+                self.draw_branches(next_id, &format!("{}{}", prefix, indent), branches, lines);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animal_structs::Sex;
+
+    fn snake(id: u32, sex: Sex, mother: Option<u32>, father: Option<u32>) -> StudbookEntry {
+        StudbookEntry {
+            id,
+            name: "snake".to_string(),
+            sex,
+            mother,
+            father,
+            born_at: 0,
+            died_at: None,
+        }
+    }
+
+    fn snake_family() -> Pedigree {
+        Pedigree::new(vec![
+            snake(1, Sex::Female, None, None),
+            snake(2, Sex::Male, None, None),
+            snake(3, Sex::Female, Some(1), Some(2)),
+            snake(4, Sex::Male, Some(1), Some(2)),
+            snake(5, Sex::Male, Some(3), None),
+        ])
+    }
+
+    #[test]
+    fn test_kinship() {
+        let mut family = snake_family();
+        family
+            .entries
+            .insert(6, snake(6, Sex::Female, Some(3), Some(4)));
+
+        assert_eq!(family.kinship(1, 2), 0.0);
+        assert_eq!(family.kinship(1, 1), 0.5);
+        assert_eq!(family.kinship(1, 3), 0.25);
+        assert_eq!(family.kinship(3, 4), 0.25);
+        assert_eq!(family.kinship(1, 5), 0.125);
+
+        assert_eq!(family.inbreeding(3), 0.0);
+        assert_eq!(family.inbreeding(6), 0.25);
+        assert_eq!(family.kinship(6, 6), 0.625);
+
+        assert_eq!(
+            family.mean_kinship(3, &[3, 4, 5]),
+            (0.5 + 0.25 + 0.25) / 3.0
+        );
+    }
+
+    #[test]
+    fn test_ancestors_tree() {
+        let pedigree = snake_family();
+        assert_eq!(
+            pedigree.ancestors_tree(5).unwrap(),
+            "snake #5 (male)
+├── mother: snake #3 (female)
+│   ├── mother: snake #1 (female)
+│   │   ├── mother: unknown
+│   │   └── father: unknown
+│   └── father: snake #2 (male)
+│       ├── mother: unknown
+│       └── father: unknown
+└── father: unknown"
+        );
+        assert!(pedigree.ancestors_tree(6).is_none());
+    }
+
+    #[test]
+    fn test_descendants_tree() {
+        let pedigree = snake_family();
+        assert_eq!(
+            pedigree.descendants_tree(1).unwrap(),
+            "snake #1 (female)
+├── snake #3 (female)
+│   └── snake #5 (male)
+└── snake #4 (male)"
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use csv::ReaderBuilder;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+use crate::file_lock::{FileLock, LockMode};
+use crate::temp_file_handler::sync_parent_directory;
+use crate::{
+    ANIMALS_ALIVE_FILE_PATH, ANIMALS_DATA_FILE_PATH, ENVIRONMENTAL_EVENTS_LOG_FILE_PATH,
+    HABITATS_FILE_PATH, JOURNAL_FILE_PATH, QUARANTINE_FILE_PATH, STUDBOOK_FILE_PATH,
+    TRANSFERS_FILE_PATH,
+};
+
+// The environmental events table is left out: written once, then edited by
+// hand, where a last line without its ending is no sign of a crash.
+const CHECKED_FILE_PATHS: [&str; 7] = [
+    ANIMALS_DATA_FILE_PATH,
+    ANIMALS_ALIVE_FILE_PATH,
+    STUDBOOK_FILE_PATH,
+    HABITATS_FILE_PATH,
+    TRANSFERS_FILE_PATH,
+    ENVIRONMENTAL_EVENTS_LOG_FILE_PATH,
+    JOURNAL_FILE_PATH,
+];
+
+/// Byte where the last record starts, if a crash cut it short: it misses
+/// its line ending or some of its fields.
+fn torn_row_start(contents: &[u8]) -> Result<Option<u64>, Box<dyn Error>> {
+    let mut reader = ReaderBuilder::new().flexible(true).from_reader(contents);
+    let fields = reader.byte_headers()?.len();
+    let mut last_row = None;
+    for result in reader.byte_records() {
+        let record = result?;
+        let start = record.position().map_or(0, |position| position.byte());
+        last_row = Some((start, record.len()));
+    }
+    Ok(last_row
+        .filter(|(_, len)| *len != fields || !contents.ends_with(b"\n"))
+        .map(|(start, _)| start))
+}
+
+/// Moves a torn last row of every ecosystem file to the quarantine file,
+/// returning the files it was taken from.
+pub fn quarantine_torn_rows() -> Result<Vec<String>, Box<dyn Error>> {
+    let _lock = FileLock::acquire(LockMode::Exclusive)?;
+    let mut quarantined = Vec::new();
+    for path in CHECKED_FILE_PATHS {
+        if quarantine_torn_row_for_path(path, QUARANTINE_FILE_PATH)? {
+            quarantined.push(path.to_string());
+        }
+    }
+    Ok(quarantined)
+}
+
+fn quarantine_torn_row_for_path(path: &str, quarantine_path: &str) -> Result<bool, Box<dyn Error>> {
+    // Read under the exclusive lock, so that the row cut off is the torn one
+    // and not one another process appended since.
+    let _lock = FileLock::acquire(LockMode::Exclusive)?;
+    let Ok(contents) = fs::read(path) else {
+        return Ok(false);
+    };
+    let Some(start) = torn_row_start(&contents)? else {
+        return Ok(false);
+    };
+
+    let torn_row = String::from_utf8_lossy(&contents[start as usize..]);
+    let mut quarantine = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(quarantine_path)?;
+    writeln!(quarantine, "{}\t{}", path, torn_row.trim_end())?;
+    quarantine.sync_data()?;
+    sync_parent_directory(quarantine_path)?;
+
+    let file = OpenOptions::new().write(true).open(path)?;
+    file.set_len(start)?;
+    file.sync_data()?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_torn_row_start() -> Result<(), Box<dyn Error>> {
+        assert_eq!(torn_row_start(b"name,sex\nrat,m\n")?, None);
+        assert_eq!(torn_row_start(b"name,sex\n")?, None);
+        assert_eq!(torn_row_start(b"name,sex\nrat,m\nrat")?, Some(15));
+        assert_eq!(torn_row_start(b"name,sex\nrat,m\nrat,\"f")?, Some(15));
+        assert_eq!(torn_row_start(b"name,sex\nrat,m\nrat\n")?, Some(15));
+        Ok(())
+    }
+
+    #[test]
+    fn test_quarantine_torn_row() -> Result<(), Box<dyn Error>> {
+        let dir = tempdir()?;
+        let path = dir.path().join("animals.csv");
+        let quarantine_path = dir.path().join("quarantine.txt");
+        let (path, quarantine_path) = (path.to_str().unwrap(), quarantine_path.to_str().unwrap());
+
+        fs::write(path, "name,sex\nrat,m\nrat,")?;
+        assert!(quarantine_torn_row_for_path(path, quarantine_path)?);
+        assert_eq!(fs::read_to_string(path)?, "name,sex\nrat,m\n");
+        assert_eq!(
+            fs::read_to_string(quarantine_path)?,
+            format!("{}\trat,\n", path)
+        );
+
+        assert!(!quarantine_torn_row_for_path(path, quarantine_path)?);
+        assert!(!quarantine_torn_row_for_path(
+            &dir.path().join("missing.csv").to_string_lossy(),
+            quarantine_path
+        )?);
+        Ok(())
+    }
+}
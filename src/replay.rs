@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+
+use crate::animal_alive_csv::ANIMALS_ALIVE_HEADERS;
+use crate::animal_data_csv::ANIMALS_DATA_HEADERS;
+use crate::animal_structs::JournalEntry;
+
+/// Species and animals alive as the journal tells them, rebuilt by playing
+/// its row changes in order.
+#[derive(PartialEq, Debug, Default)]
+pub struct Projection {
+    pub species: BTreeMap<String, Vec<String>>,
+    pub animals: BTreeMap<u32, Vec<String>>,
+}
+
+impl Projection {
+    pub fn from_journal(entries: &[JournalEntry]) -> Projection {
+        let mut projection = Projection::default();
+        for entry in entries {
+            projection.apply(entry);
+        }
+        projection
+    }
+
+    pub fn apply(&mut self, entry: &JournalEntry) {
+        if !entry.changes_row() {
+            return;
+        }
+        match (entry.individual, &entry.after) {
+            (Some(id), Some(row)) => {
+                self.animals.insert(id, row.clone());
+            }
+            (Some(id), None) => {
+                self.animals.remove(&id);
+            }
+            (None, Some(row)) => {
+                self.species.insert(entry.species.clone(), row.clone());
+            }
+            (None, None) => {
+                self.species.remove(&entry.species);
+            }
+        }
+    }
+
+    /// How the rows kept in the files differ from the rebuilt ones.
+    pub fn divergences(
+        &self,
+        species_rows: &[Vec<String>],
+        animal_rows: &[Vec<String>],
+    ) -> Vec<String> {
+        let species = species_rows
+            .iter()
+            .map(|row| (row[0].clone(), row.clone()))
+            .collect::<BTreeMap<_, _>>();
+        let animals = animal_rows
+            .iter()
+            .filter_map(|row| Some((row[2].parse::<u32>().ok()?, row.clone())))
+            .collect::<BTreeMap<_, _>>();
+        let mut divergences = compare(&self.species, &species, &ANIMALS_DATA_HEADERS, |name| {
+            name.clone()
+        });
+        divergences.extend(compare(
+            &self.animals,
+            &animals,
+            &ANIMALS_ALIVE_HEADERS,
+            |id| format!("#{}", id),
+        ));
+        divergences
+    }
+}
+
+fn compare<K: Ord>(
+    replayed: &BTreeMap<K, Vec<String>>,
+    current: &BTreeMap<K, Vec<String>>,
+    headers: &[&str],
+    label: impl Fn(&K) -> String,
+) -> Vec<String> {
+    let mut divergences = Vec::new();
+    for (key, row) in replayed {
+        match current.get(key) {
+            None => divergences.push(format!(
+                "{} is in the journal but not in the files",
+                label(key)
+            )),
+            Some(current) => {
+                for (index, header) in headers.iter().enumerate() {
+                    let (expected, found) = (row.get(index), current.get(index));
+                    if expected != found {
+                        divergences.push(format!(
+                            "{}: {} is {} in the files but {} in the journal",
+                            label(key),
+                            header,
+                            found.map_or("missing", |found| found.as_str()),
+                            expected.map_or("missing", |expected| expected.as_str())
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    for key in current.keys().filter(|key| !replayed.contains_key(key)) {
+        divergences.push(format!(
+            "{} is in the files but not in the journal",
+            label(key)
+        ));
+    }
+    divergences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::animal_structs::animals_alive_models::*;
+    use crate::animal_structs::animals_data_models::*;
+
+    fn entry(
+        species: &str,
+        individual: Option<u32>,
+        before: Option<Vec<String>>,
+        after: Option<Vec<String>>,
+    ) -> JournalEntry {
+        JournalEntry {
+            recorded_at: 0,
+            operation: 1,
+            command: "test".to_string(),
+            species: species.to_string(),
+            individual,
+            outcome: "changed".to_string(),
+            cause: String::new(),
+            before,
+            after,
+        }
+    }
+
+    #[test]
+    fn test_replay_and_divergences() {
+        let snake = snake_data().to_csv().to_vec();
+        let female = snake_female().to_csv().to_vec();
+        let male = snake_male().to_csv().to_vec();
+        let mut hungry_male = male.clone();
+        hungry_male[3] = "40".to_string();
+        let projection = Projection::from_journal(&[
+            entry("snake", None, None, Some(snake.clone())),
+            entry("snake", Some(1), None, Some(female.clone())),
+            entry("snake", Some(2), None, Some(male.clone())),
+            entry(
+                "snake",
+                Some(2),
+                Some(male.clone()),
+                Some(hungry_male.clone()),
+            ),
+            entry("snake", Some(1), Some(female.clone()), None),
+            entry("snake", Some(2), None, None),
+        ]);
+        assert_eq!(projection.species.len(), 1);
+        assert_eq!(projection.animals.len(), 1);
+        assert_eq!(projection.animals[&2], hungry_male);
+
+        assert!(projection
+            .divergences(std::slice::from_ref(&snake), &[hungry_male])
+            .is_empty());
+        assert_eq!(
+            projection.divergences(&[snake], &[male, female]),
+            vec![
+                "#2: energy is 100 in the files but 40 in the journal".to_string(),
+                "#1 is in the files but not in the journal".to_string(),
+            ]
+        );
+    }
+}
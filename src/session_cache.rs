@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::SystemTime;
+
+use crate::animal_alive_csv::AnimalAliveReader;
+use crate::animal_data_csv::AnimalDataReader;
+use crate::animal_structs::*;
+use crate::custom_writers_and_readers::create_reader_for_path;
+use crate::file_lock::{FileLock, LockMode};
+use crate::transaction::Transaction;
+use crate::{ANIMALS_ALIVE_FILE_PATH, ANIMALS_DATA_FILE_PATH};
+
+pub static SPECIES: Cache<SpeciesIndex> = Cache::new(ANIMALS_DATA_FILE_PATH);
+pub static ANIMALS_ALIVE: Cache<AnimalsIndex> = Cache::new(ANIMALS_ALIVE_FILE_PATH);
+
+/// Size and last change of a file, telling whether it changed since loaded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct FileStamp {
+    modified: SystemTime,
+    len: u64,
+}
+
+impl FileStamp {
+    fn of(path: &str) -> Result<FileStamp, Box<dyn Error>> {
+        let metadata = fs::metadata(path)?;
+        Ok(FileStamp {
+            modified: metadata.modified()?,
+            len: metadata.len(),
+        })
+    }
+}
+
+/// Rows of a file, indexed for the lookups commands make.
+pub trait Index: Sized {
+    type Row: Clone;
+    fn load(path: &str) -> Result<Vec<Self::Row>, Box<dyn Error>>;
+    fn new(rows: Vec<Self::Row>) -> Self;
+    fn rows(&self) -> &[Self::Row];
+}
+
+pub struct SpeciesIndex {
+    species: Vec<AnimalData>,
+    by_name: HashMap<String, usize>,
+}
+
+impl Index for SpeciesIndex {
+    type Row = AnimalData;
+
+    fn load(path: &str) -> Result<Vec<AnimalData>, Box<dyn Error>> {
+        create_reader_for_path(path)?.read_animals_data()
+    }
+
+    fn new(species: Vec<AnimalData>) -> SpeciesIndex {
+        let mut by_name = HashMap::new();
+        for (index, animal_data) in species.iter().enumerate() {
+            by_name.entry(animal_data.name.clone()).or_insert(index);
+        }
+        SpeciesIndex { species, by_name }
+    }
+
+    fn rows(&self) -> &[AnimalData] {
+        &self.species
+    }
+}
+
+pub struct AnimalsIndex {
+    animals: Vec<AnimalAlive>,
+    by_name: HashMap<String, Vec<usize>>,
+    by_id: HashMap<u32, usize>,
+}
+
+impl AnimalsIndex {
+    fn individuals(&self, animal_name: &str) -> impl Iterator<Item = &AnimalAlive> {
+        self.by_name
+            .get(animal_name)
+            .into_iter()
+            .flatten()
+            .map(|&index| &self.animals[index])
+    }
+}
+
+impl Index for AnimalsIndex {
+    type Row = AnimalAlive;
+
+    fn load(path: &str) -> Result<Vec<AnimalAlive>, Box<dyn Error>> {
+        create_reader_for_path(path)?.read_animals_alive()
+    }
+
+    fn new(animals: Vec<AnimalAlive>) -> AnimalsIndex {
+        let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut by_id = HashMap::new();
+        for (index, animal) in animals.iter().enumerate() {
+            by_name.entry(animal.name.clone()).or_default().push(index);
+            by_id.insert(animal.id, index);
+        }
+        AnimalsIndex {
+            animals,
+            by_name,
+            by_id,
+        }
+    }
+
+    fn rows(&self) -> &[AnimalAlive] {
+        &self.animals
+    }
+}
+
+/// Index of a file kept for the whole session. Reads are served from it
+/// while the file stays as this process last left it; a file changed by
+/// another process or by hand is loaded again.
+pub struct Cache<I> {
+    path: &'static str,
+    cached: Mutex<Option<(FileStamp, Arc<I>)>>,
+}
+
+impl<I: Index> Cache<I> {
+    pub const fn new(path: &'static str) -> Cache<I> {
+        Cache {
+            path,
+            cached: Mutex::new(None),
+        }
+    }
+
+    pub fn get(&self) -> Result<Arc<I>, Box<dyn Error>> {
+        let mut cached = self.cached.lock().map_err(|_| "Session cache poisoned")?;
+        // Stamped under the lock so no write slips in between.
+        let _lock = FileLock::acquire(LockMode::Shared)?;
+        let stamp = FileStamp::of(self.path)?;
+        if let Some((cached_stamp, index)) = cached.as_ref() {
+            if *cached_stamp == stamp {
+                return Ok(Arc::clone(index));
+            }
+        }
+        let index = Arc::new(I::new(I::load(self.path)?));
+        *cached = Some((stamp, Arc::clone(&index)));
+        Ok(index)
+    }
+
+    /// Makes to the index a change just written to the file. The caller
+    /// holds the ecosystem exclusively, with `lock`, since it last called
+    /// `get`, so the index matched the file before the write. If the change
+    /// can't be made, the index is dropped to be loaded again.
+    pub fn write_through<F>(&self, lock: &FileLock, change: F) -> Result<(), Box<dyn Error>>
+    where
+        F: FnOnce(&mut Vec<I::Row>),
+    {
+        if lock.mode() != LockMode::Exclusive {
+            return Err("Writing through needs the ecosystem locked exclusively".into());
+        }
+        let mut cached = self.cached.lock().map_err(|_| "Session cache poisoned")?;
+        if let Some((_, index)) = cached.take() {
+            let mut rows = index.rows().to_vec();
+            change(&mut rows);
+            *cached = Some((FileStamp::of(self.path)?, Arc::new(I::new(rows))));
+        }
+        Ok(())
+    }
+
+    /// Drops the index, so that the next `get` loads the file again.
+    fn invalidate(&self) {
+        *self.cached.lock().unwrap_or_else(PoisonError::into_inner) = None;
+        self.cached.clear_poison();
+    }
+
+    /// Writes a change staged in `transaction` through once it commits.
+    pub fn write_through_on_commit<F>(
+        &'static self,
+        transaction: &mut Transaction,
+        change: F,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: FnOnce(&mut Vec<I::Row>) + 'static,
+    {
+        self.get()?;
+        transaction.after_commit(move |lock| {
+            self.write_through(lock, change).map_err(|error| {
+                self.invalidate();
+                format!("Session cache dropped, to be loaded again: {}", error).into()
+            })
+        });
+        Ok(())
+    }
+}
+
+impl AnimalDataReader for Arc<SpeciesIndex> {
+    fn read_animal_data(
+        &mut self,
+        animal_name: &str,
+    ) -> Result<Option<AnimalData>, Box<dyn Error>> {
+        Ok(self
+            .by_name
+            .get(animal_name)
+            .map(|&index| self.species[index].clone()))
+    }
+    fn read_animals_data(&mut self) -> Result<Vec<AnimalData>, Box<dyn Error>> {
+        Ok(self.species.clone())
+    }
+}
+
+impl AnimalAliveReader for Arc<AnimalsIndex> {
+    fn read_animal_alive(
+        &mut self,
+        animal_name: &str,
+    ) -> Result<Option<AnimalAlive>, Box<dyn Error>> {
+        Ok(self.individuals(animal_name).next().cloned())
+    }
+    fn read_animal_alive_by_id(&mut self, id: u32) -> Result<Option<AnimalAlive>, Box<dyn Error>> {
+        Ok(self
+            .by_id
+            .get(&id)
+            .map(|&index| self.animals[index].clone()))
+    }
+    fn read_animals_alive(&mut self) -> Result<Vec<AnimalAlive>, Box<dyn Error>> {
+        Ok(self.animals.clone())
+    }
+    /// Whether there are two adults able to act as mother and father, a
+    /// hermaphrodite being able to act as either.
+    fn has_both_sexes(&mut self, animal_name: &str) -> Result<bool, Box<dyn Error>> {
+        let (mut males, mut females, mut hermaphrodites) = (0, 0, 0);
+        for animal in self.individuals(animal_name) {
+            if animal.stage == LifeStage::Adult {
+                match animal.sex {
+                    Sex::Male => males += 1,
+                    Sex::Female => females += 1,
+                    Sex::Hermaphrodite => hermaphrodites += 1,
+                }
+            }
+        }
+        Ok(males + hermaphrodites > 0
+            && females + hermaphrodites > 0
+            && males + females + hermaphrodites >= 2)
+    }
+    fn count_animal(&mut self, animal_name: &str) -> Result<i32, Box<dyn Error>> {
+        Ok(self.individuals(animal_name).count() as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    use crate::animal_alive_csv::ANIMALS_ALIVE_HEADERS;
+    use crate::animal_structs::animals_alive_models::*;
+    use crate::animal_structs::animals_data_models::*;
+
+    #[test]
+    fn test_indexes() -> Result<(), Box<dyn Error>> {
+        let mut species = Arc::new(SpeciesIndex::new(vec![snake_data(), chameleon_data()]));
+        assert_eq!(
+            species.read_animal_data("chameleon")?,
+            Some(chameleon_data())
+        );
+        assert_eq!(species.read_animal_data("rat")?, None);
+
+        let mut animals = Arc::new(AnimalsIndex::new(vec![
+            snake_female(),
+            chameleon_male(),
+            snake_male(),
+        ]));
+        assert_eq!(animals.read_animal_alive("snake")?, Some(snake_female()));
+        assert_eq!(
+            animals.read_animal_alive_by_id(snake_male().id)?,
+            Some(snake_male())
+        );
+        assert_eq!(animals.count_animal("snake")?, 2);
+        assert!(animals.has_both_sexes("snake")?);
+        assert!(!animals.has_both_sexes("chameleon")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_follows_the_file() -> Result<(), Box<dyn Error>> {
+        let dir = tempdir()?;
+        let path = dir.path().join("animals_alive.csv");
+        let path: &'static str = Box::leak(path.to_string_lossy().into_owned().into_boxed_str());
+        let row = |animal: AnimalAlive| format!("{}\n", animal.to_csv().join(","));
+        fs::write(
+            path,
+            format!(
+                "{}\n{}",
+                ANIMALS_ALIVE_HEADERS.join(","),
+                row(snake_female())
+            ),
+        )?;
+        let cache: &'static Cache<AnimalsIndex> = Box::leak(Box::new(Cache::new(path)));
+        let lock_path = dir.path().join("ecosystem.lock");
+        let exclusive = || {
+            FileLock::acquire_for_path(
+                lock_path.to_str().unwrap(),
+                LockMode::Exclusive,
+                Duration::ZERO,
+            )
+        };
+        assert_eq!(cache.get()?.count_animal("snake")?, 1);
+
+        // Written through, the index is served without the file having it.
+        let lock = exclusive()?;
+        cache.write_through(&lock, |animals| animals.push(snake_male()))?;
+        drop(lock);
+        assert_eq!(cache.get()?.count_animal("snake")?, 2);
+
+        // Only an exclusive lock lets a change be written through.
+        let lock = FileLock::acquire_for_path(
+            lock_path.to_str().unwrap(),
+            LockMode::Shared,
+            Duration::ZERO,
+        )?;
+        assert!(cache.write_through(&lock, Vec::clear).is_err());
+        drop(lock);
+
+        // Changed behind our back, the file is loaded again.
+        let mut file = fs::OpenOptions::new().append(true).open(path)?;
+        file.write_all(row(chameleon_male()).as_bytes())?;
+        let mut animals = cache.get()?;
+        assert_eq!(animals.count_animal("snake")?, 1);
+        assert_eq!(animals.count_animal("chameleon")?, 1);
+
+        // A change that can't be written through drops the index.
+        fs::remove_file(path)?;
+        let lock = exclusive()?;
+        assert!(cache.write_through(&lock, Vec::clear).is_err());
+        drop(lock);
+        assert!(cache.cached.lock().unwrap().is_none());
+        Ok(())
+    }
+}
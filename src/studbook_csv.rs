@@ -0,0 +1,241 @@
+use csv::StringRecord;
+use std::error::Error;
+use std::fs::File;
+
+use crate::animal_alive_csv::sex_from_csv;
+use crate::animal_csv_shared_functions::{header_index, optional_from_csv, optional_to_csv};
+use crate::animal_structs::*;
+use crate::clock;
+use crate::custom_writers_and_readers::*;
+use crate::transaction::Transaction;
+use crate::STUDBOOK_FILE_PATH;
+
+pub const STUDBOOK_HEADERS: [&str; 7] = [
+    "name", "sex", "id", "mother", "father", "born_at", "died_at",
+];
+
+pub const STUDBOOK_EXPORT_HEADERS: [&str; 6] = [
+    "Studbook ID",
+    "Sex",
+    "Birth Date",
+    "Sire",
+    "Dam",
+    "Death Date",
+];
+
+impl StudbookEntry {
+    pub fn to_csv(&self) -> [String; 7] {
+        [
+            self.name.clone(),
+            self.sex.as_str().to_string(),
+            self.id.to_string(),
+            optional_to_csv(self.mother),
+            optional_to_csv(self.father),
+            self.born_at.to_string(),
+            optional_to_csv(self.died_at),
+        ]
+    }
+
+    pub fn from_csv(record: &StringRecord) -> Result<StudbookEntry, Box<dyn Error>> {
+        Ok(StudbookEntry {
+            id: record[2].parse()?,
+            name: record[0].to_string(),
+            sex: sex_from_csv(&record[1])?,
+            mother: optional_from_csv(&record[3])?,
+            father: optional_from_csv(&record[4])?,
+            born_at: record[5].parse()?,
+            died_at: optional_from_csv(&record[6])?,
+        })
+    }
+
+    /// Row of a studbook as exchanged between zoos: parents are sire and dam,
+    /// unknown ones written as `UNK`.
+    pub fn to_export_csv(&self) -> [String; 6] {
+        let parent =
+            |parent: Option<u32>| parent.map(|id| id.to_string()).unwrap_or("UNK".to_string());
+        let sex = match self.sex {
+            Sex::Male => "M",
+            Sex::Female => "F",
+            Sex::Hermaphrodite => "H",
+        };
+        [
+            self.id.to_string(),
+            sex.to_string(),
+            clock::format_date(self.born_at),
+            parent(self.father),
+            parent(self.mother),
+            optional_to_csv(self.died_at.map(clock::format_date)),
+        ]
+    }
+}
+
+impl<W: Durable> CustomWriter<W> {
+    pub fn write_studbook_entry(&mut self, entry: &StudbookEntry) -> csv::Result<()> {
+        self.inner.write_record(entry.to_csv())?;
+        self.flush()?;
+        Ok(())
+    }
+}
+
+pub trait StudbookReader {
+    fn read_studbook(&mut self) -> Result<Vec<StudbookEntry>, Box<dyn Error>>;
+    fn next_animal_id(&mut self) -> Result<u32, Box<dyn Error>>;
+}
+
+impl<R: std::io::Read + std::io::Seek> StudbookReader for CustomReader<R> {
+    fn read_studbook(&mut self) -> Result<Vec<StudbookEntry>, Box<dyn Error>> {
+        let mut entries = Vec::new();
+        for result in self.inner.records() {
+            entries.push(StudbookEntry::from_csv(&result?)?);
+        }
+        self.seek_to_beginning()?;
+        Ok(entries)
+    }
+    fn next_animal_id(&mut self) -> Result<u32, Box<dyn Error>> {
+        let mut last_id = 0;
+        for result in self.inner.records() {
+            let id: u32 = result?[2].parse()?;
+            last_id = last_id.max(id);
+        }
+        self.seek_to_beginning()?;
+        Ok(last_id + 1)
+    }
+}
+
+/// Stages newborns' entries at the end of the studbook.
+pub fn register_births(
+    transaction: &mut Transaction,
+    entries: &[StudbookEntry],
+) -> Result<(), Box<dyn Error>> {
+    let rows = entries
+        .iter()
+        .map(|entry| entry.to_csv().to_vec())
+        .collect::<Vec<_>>();
+    transaction.append_all(STUDBOOK_FILE_PATH, &rows)
+}
+
+pub fn record_deaths(
+    transaction: &mut Transaction,
+    ids: &[u32],
+    died_at: u64,
+) -> Result<(), Box<dyn Error>> {
+    record_deaths_for_path(transaction, ids, died_at, STUDBOOK_FILE_PATH)
+}
+
+fn record_deaths_for_path(
+    transaction: &mut Transaction,
+    ids: &[u32],
+    died_at: u64,
+    path: &str,
+) -> Result<(), Box<dyn Error>> {
+    transaction.rewrite(path, |headers, record| {
+        let id: u32 = record[header_index(headers, "id")?].parse()?;
+        let died_at_index = header_index(headers, "died_at")?;
+        if ids.contains(&id) && record[died_at_index].is_empty() {
+            let mut record = record.iter().collect::<Vec<_>>();
+            let died_at = died_at.to_string();
+            record[died_at_index] = &died_at;
+            Ok(Some(StringRecord::from(record)))
+        } else {
+            Ok(Some(record))
+        }
+    })
+}
+
+/// Forgets the deaths of animals brought back to life by an undo.
+pub fn record_revivals(transaction: &mut Transaction, ids: &[u32]) -> Result<(), Box<dyn Error>> {
+    record_revivals_for_path(transaction, ids, STUDBOOK_FILE_PATH)
+}
+
+fn record_revivals_for_path(
+    transaction: &mut Transaction,
+    ids: &[u32],
+    path: &str,
+) -> Result<(), Box<dyn Error>> {
+    transaction.rewrite(path, |headers, record| {
+        let id: u32 = record[header_index(headers, "id")?].parse()?;
+        let died_at_index = header_index(headers, "died_at")?;
+        if ids.contains(&id) {
+            let mut record = record.iter().collect::<Vec<_>>();
+            record[died_at_index] = "";
+            Ok(Some(StringRecord::from(record)))
+        } else {
+            Ok(Some(record))
+        }
+    })
+}
+
+pub fn export_studbook(animal_name: &str, path: &str) -> Result<usize, Box<dyn Error>> {
+    let entries = reader_studbook()?
+        .read_studbook()?
+        .into_iter()
+        .filter(|entry| entry.name == animal_name)
+        .collect::<Vec<_>>();
+    let mut writer = create_writer_truncate_for_path(path)?;
+    writer.inner.write_record(STUDBOOK_EXPORT_HEADERS)?;
+    for entry in &entries {
+        writer.inner.write_record(entry.to_export_csv())?;
+    }
+    writer.flush()?;
+    Ok(entries.len())
+}
+
+pub fn writer_studbook() -> Result<CustomWriter<File>, Box<dyn Error>> {
+    create_writer_append_for_path(STUDBOOK_FILE_PATH)
+}
+
+pub fn reader_studbook() -> Result<CustomReader<File>, Box<dyn Error>> {
+    create_reader_for_path(STUDBOOK_FILE_PATH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::animal_structs::animals_alive_models::*;
+    use crate::csv_files_creator::create_test_studbook;
+    use crate::custom_writers_and_readers::{reader_for_test, writer_for_test};
+    use crate::{test_dir, test_path};
+
+    #[test]
+    fn test_read_studbook() -> Result<(), Box<dyn Error>> {
+        create_test_studbook()?;
+
+        let mut writer = writer_for_test()?;
+
+        let mother = StudbookEntry::registered(&snake_female(), None, None);
+        let father = StudbookEntry::registered(&snake_male(), None, None);
+        let mut child = StudbookEntry::registered(&chameleon_male(), Some(1), Some(2));
+        writer.write_studbook_entry(&mother)?;
+        writer.write_studbook_entry(&father)?;
+        writer.write_studbook_entry(&child)?;
+
+        let mut transaction = Transaction::in_dir(&test_dir());
+        record_deaths_for_path(&mut transaction, &[3], 42, &test_path())?;
+        transaction.commit()?;
+        child.died_at = Some(42);
+
+        let mut reader = reader_for_test()?;
+
+        assert_eq!(reader.read_studbook()?, vec![mother, father, child]);
+        assert_eq!(reader.next_animal_id()?, 4);
+
+        let mut transaction = Transaction::in_dir(&test_dir());
+        record_revivals_for_path(&mut transaction, &[3], &test_path())?;
+        transaction.commit()?;
+        reader = reader_for_test()?;
+        assert_eq!(reader.read_studbook()?[2].died_at, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_export_csv() {
+        let mut child = StudbookEntry::registered(&snake_male(), Some(1), None);
+        child.born_at = 0;
+        child.died_at = Some(clock::SECONDS_PER_DAY);
+        assert_eq!(
+            child.to_export_csv(),
+            ["2", "M", "1970-01-01", "UNK", "1", "1970-01-02"].map(String::from)
+        );
+    }
+}
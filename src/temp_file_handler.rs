@@ -1,4 +1,6 @@
 use std::error::Error;
+use std::fs::File;
+use std::io::Write;
 use std::path::Path;
 use tempfile::{Builder, NamedTempFile};
 
@@ -12,3 +14,45 @@ pub fn create_temp_file(path: &str) -> Result<NamedTempFile, Box<dyn Error>> {
     let temp_dir = temp_dir_path_from_path(path)?;
     Ok(Builder::new().tempfile_in(temp_dir)?)
 }
+
+/// Makes files created, renamed or removed next to `path` survive a crash.
+pub fn sync_parent_directory(path: &str) -> Result<(), Box<dyn Error>> {
+    let directory = temp_dir_path_from_path(path)?;
+    let directory = if directory.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        directory
+    };
+    File::open(directory)?.sync_all()?;
+    Ok(())
+}
+
+/// Replaces the contents of `path` through a temp file renamed over it, so
+/// that a crash leaves either the old contents or the new ones.
+pub fn write_file_durably(path: &str, contents: &str) -> Result<(), Box<dyn Error>> {
+    let mut temp_file = create_temp_file(path)?;
+    temp_file.write_all(contents.as_bytes())?;
+    temp_file.as_file().sync_all()?;
+    temp_file.persist(path)?;
+    sync_parent_directory(path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_write_file_durably() -> Result<(), Box<dyn Error>> {
+        let dir = tempdir()?;
+        let path = dir.path().join("clock.txt");
+        let path = path.to_str().unwrap();
+        write_file_durably(path, "12")?;
+        write_file_durably(path, "34")?;
+        assert_eq!(fs::read_to_string(path)?, "34");
+        assert_eq!(fs::read_dir(dir.path())?.count(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,330 @@
+use csv::StringRecord;
+use std::cell::RefCell;
+use std::error::Error;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+use crate::custom_writers_and_readers::{create_reader_for_path, create_writer_append_for_path};
+use crate::file_lock::{FileLock, LockMode};
+use crate::temp_file_handler::{create_temp_file, sync_parent_directory};
+use crate::TRANSACTION_FILE_PATH;
+
+type CommitHook = Box<dyn FnOnce(&FileLock) -> Result<(), Box<dyn Error>>>;
+
+thread_local! {
+    /// What failing hooks reported since the command layer last asked.
+    static COMMIT_WARNINGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Takes the warnings of hooks that failed after their changes were saved,
+/// for the command to add to its output.
+pub fn take_commit_warnings() -> Vec<String> {
+    COMMIT_WARNINGS.with_borrow_mut(std::mem::take)
+}
+
+/// Changes to several CSV files, staged in temp files next to them and
+/// renamed over them together on commit. The renames are listed in an
+/// intent file first, so that if they are cut short, the next start can
+/// finish them: either every file changes or none does. The ecosystem stays
+/// locked from the first file staged until the commit.
+pub struct Transaction {
+    staged: Vec<(String, NamedTempFile)>,
+    intent_path: String,
+    lock: Option<FileLock>,
+    after_commit: Vec<CommitHook>,
+}
+
+impl Default for Transaction {
+    fn default() -> Self {
+        Transaction::new()
+    }
+}
+
+impl Transaction {
+    pub fn new() -> Transaction {
+        Transaction {
+            staged: Vec::new(),
+            intent_path: TRANSACTION_FILE_PATH.to_string(),
+            lock: None,
+            after_commit: Vec::new(),
+        }
+    }
+
+    /// Transaction keeping its intent file in `dir`, so tests committing at
+    /// once don't share one.
+    #[cfg(test)]
+    pub fn in_dir(dir: &Path) -> Transaction {
+        Transaction {
+            intent_path: dir.join("transaction.txt").to_string_lossy().into_owned(),
+            ..Transaction::new()
+        }
+    }
+
+    /// Latest version of a file in the transaction, staged or not.
+    fn source(&self, path: &str) -> String {
+        self.staged
+            .iter()
+            .find(|(target, _)| target == path)
+            .map(|(_, temp_file)| temp_file.path().to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_string())
+    }
+
+    fn stage<F>(
+        &mut self,
+        path: &str,
+        new_headers: Option<&[&str]>,
+        mut rewrite: F,
+        appended: &[&[String]],
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: FnMut(&StringRecord, StringRecord) -> Result<Option<StringRecord>, Box<dyn Error>>,
+    {
+        if self.lock.is_none() {
+            self.lock = Some(FileLock::acquire(LockMode::Exclusive)?);
+        }
+        let temp_file = create_temp_file(path)?;
+        let temp_file_path = temp_file.path().to_str().ok_or("Invalid temp file path")?;
+        let mut temp_file_writer = create_writer_append_for_path(temp_file_path)?;
+
+        let mut source_reader = create_reader_for_path(&self.source(path))?;
+
+        let headers = source_reader.inner.headers()?.clone();
+        match new_headers {
+            Some(new_headers) => temp_file_writer.inner.write_record(new_headers)?,
+            None => temp_file_writer.inner.write_record(&headers)?,
+        }
+
+        for result in source_reader.inner.records() {
+            if let Some(record) = rewrite(&headers, result?)? {
+                temp_file_writer.inner.write_record(&record)?;
+            }
+        }
+        for record in appended {
+            temp_file_writer.inner.write_record(*record)?;
+        }
+
+        temp_file_writer.flush()?;
+
+        // An older staged version is dropped, deleting its temp file.
+        self.staged.retain(|(target, _)| target != path);
+        self.staged.push((path.to_string(), temp_file));
+        Ok(())
+    }
+
+    /// Stages `path` keeping the records for which `rewrite` returns `Some`.
+    /// The headers are handed to `rewrite` so it can locate its columns.
+    pub fn rewrite<F>(&mut self, path: &str, rewrite: F) -> Result<(), Box<dyn Error>>
+    where
+        F: FnMut(&StringRecord, StringRecord) -> Result<Option<StringRecord>, Box<dyn Error>>,
+    {
+        self.stage(path, None, rewrite, &[])
+    }
+
+    /// Stages `path` with new headers, every record being converted to them
+    /// by `migrate`, which is handed the old headers.
+    pub fn migrate<F>(
+        &mut self,
+        path: &str,
+        new_headers: &[&str],
+        mut migrate: F,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: FnMut(&StringRecord, StringRecord) -> Result<StringRecord, Box<dyn Error>>,
+    {
+        self.stage(
+            path,
+            Some(new_headers),
+            |headers, record| migrate(headers, record).map(Some),
+            &[],
+        )
+    }
+
+    /// Stages `path` with a record added at its end.
+    pub fn append(&mut self, path: &str, record: &[String]) -> Result<(), Box<dyn Error>> {
+        self.stage(path, None, |_, record| Ok(Some(record)), &[record])
+    }
+
+    /// Stages `path` with records added at its end.
+    pub fn append_all(
+        &mut self,
+        path: &str,
+        records: &[Vec<String>],
+    ) -> Result<(), Box<dyn Error>> {
+        let records = records.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        self.stage(path, None, |_, record| Ok(Some(record)), &records)
+    }
+
+    /// Runs `hook` once the files are renamed, with the ecosystem still locked.
+    /// The files being committed by then, a failing hook only leaves a
+    /// warning for `take_commit_warnings`.
+    pub fn after_commit<F>(&mut self, hook: F)
+    where
+        F: FnOnce(&FileLock) -> Result<(), Box<dyn Error>> + 'static,
+    {
+        self.after_commit.push(Box::new(hook));
+    }
+
+    pub fn commit(self) -> Result<(), Box<dyn Error>> {
+        let lock = match self.lock {
+            Some(lock) => lock,
+            None => FileLock::acquire(LockMode::Exclusive)?,
+        };
+        let intent = self
+            .staged
+            .iter()
+            .map(|(target, temp_file)| format!("{}\t{}\n", temp_file.path().display(), target))
+            .collect::<String>();
+        let mut intent_file = create_temp_file(&self.intent_path)?;
+        intent_file.write_all(intent.as_bytes())?;
+        intent_file.as_file().sync_all()?;
+        intent_file.persist(&self.intent_path)?;
+        sync_parent_directory(&self.intent_path)?;
+        // Until the intent file lists them, the temp files are removed when
+        // dropped; from now on, the next start renames them if we don't.
+        for (_, temp_file) in self.staged {
+            temp_file.keep()?;
+        }
+
+        finish_renames(&self.intent_path)?;
+        for hook in self.after_commit {
+            if let Err(error) = hook(&lock) {
+                COMMIT_WARNINGS.with_borrow_mut(|warnings| warnings.push(error.to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Renames what the intent file lists, then forgets about it. Renames made
+/// before an interruption are skipped, their temp files being gone. The
+/// intent file only goes once the renames are synced to disk.
+fn finish_renames(intent_path: &str) -> Result<(), Box<dyn Error>> {
+    for line in fs::read_to_string(intent_path)?.lines() {
+        let (temp_file_path, target) = line.split_once('\t').ok_or("Invalid transaction")?;
+        if Path::new(temp_file_path).exists() {
+            fs::rename(temp_file_path, target)?;
+        }
+        sync_parent_directory(target)?;
+    }
+    fs::remove_file(intent_path)?;
+    sync_parent_directory(intent_path)?;
+    Ok(())
+}
+
+/// Finishes a transaction interrupted while committing, returning whether
+/// there was one.
+pub fn recover_transaction() -> Result<bool, Box<dyn Error>> {
+    recover_transaction_for_path(TRANSACTION_FILE_PATH)
+}
+
+fn recover_transaction_for_path(intent_path: &str) -> Result<bool, Box<dyn Error>> {
+    if !Path::new(intent_path).exists() {
+        return Ok(false);
+    }
+    let _lock = FileLock::acquire(LockMode::Exclusive)?;
+    // Another process may have finished it while we waited.
+    if !Path::new(intent_path).exists() {
+        return Ok(false);
+    }
+    finish_renames(intent_path)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use tempfile::tempdir;
+
+    fn read(path: &Path) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_commit_changes_every_file() -> Result<(), Box<dyn Error>> {
+        let dir = tempdir()?;
+        let species = dir.path().join("species.csv");
+        let animals = dir.path().join("animals.csv");
+        fs::write(&species, "name\nsnake\nrat\n")?;
+        fs::write(&animals, "name,id\nsnake,1\nrat,2\nrat,3\n")?;
+        let intent_path = dir.path().join("transaction.txt");
+        let mut transaction = Transaction::in_dir(dir.path());
+
+        let not_rat =
+            |_: &StringRecord, record: StringRecord| Ok((&record[0] != "rat").then_some(record));
+        transaction.rewrite(species.to_str().unwrap(), not_rat)?;
+        transaction.rewrite(animals.to_str().unwrap(), not_rat)?;
+        transaction.append(
+            animals.to_str().unwrap(),
+            &["cow".to_string(), "4".to_string()],
+        )?;
+        assert_eq!(read(&species), "name\nsnake\nrat\n");
+        // A failing hook doesn't undo the commit, nor keep the next from running.
+        transaction.after_commit(|_| Err("Session cache poisoned".into()));
+        let hook_locked = Rc::new(Cell::new(false));
+        let locked = Rc::clone(&hook_locked);
+        transaction.after_commit(move |lock| {
+            locked.set(lock.mode() == LockMode::Exclusive);
+            Ok(())
+        });
+
+        transaction.commit()?;
+        assert!(hook_locked.get());
+        assert_eq!(take_commit_warnings(), ["Session cache poisoned"]);
+        assert!(take_commit_warnings().is_empty());
+        assert_eq!(read(&species), "name\nsnake\n");
+        assert_eq!(read(&animals), "name,id\nsnake,1\ncow,4\n");
+        assert!(!intent_path.exists());
+        assert_eq!(fs::read_dir(dir.path())?.count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_recover_interrupted_commit() -> Result<(), Box<dyn Error>> {
+        let dir = tempdir()?;
+        let species = dir.path().join("species.csv");
+        let animals = dir.path().join("animals.csv");
+        fs::write(&species, "name\nsnake\n")?;
+        fs::write(&animals, "name,id\nrat,2\n")?;
+        // The species file was renamed before the interruption, not the
+        // animals one.
+        let staged_animals = dir.path().join("staged.csv");
+        fs::write(&staged_animals, "name,id\n")?;
+        let intent_path = dir.path().join("transaction.txt");
+        fs::write(
+            &intent_path,
+            format!(
+                "{}\t{}\n{}\t{}\n",
+                dir.path().join("gone.csv").display(),
+                species.display(),
+                staged_animals.display(),
+                animals.display()
+            ),
+        )?;
+
+        let intent_path = intent_path.to_str().unwrap();
+        assert!(recover_transaction_for_path(intent_path)?);
+        assert_eq!(read(&species), "name\nsnake\n");
+        assert_eq!(read(&animals), "name,id\n");
+        assert!(!recover_transaction_for_path(intent_path)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_commit_leaves_no_temp_files() -> Result<(), Box<dyn Error>> {
+        let dir = tempdir()?;
+        let species = dir.path().join("species.csv");
+        fs::write(&species, "name\nsnake\n")?;
+        // The intent file can't be written in a directory that doesn't exist.
+        let mut transaction = Transaction::in_dir(&dir.path().join("missing"));
+        transaction.append(species.to_str().unwrap(), &["rat".to_string()])?;
+
+        assert!(transaction.commit().is_err());
+        assert_eq!(read(&species), "name\nsnake\n");
+        assert_eq!(fs::read_dir(dir.path())?.count(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use csv::StringRecord;
+use std::error::Error;
+use std::fs::File;
+
+use crate::animal_csv_shared_functions::{optional_from_csv, optional_to_csv};
+use crate::animal_structs::Transfer;
+use crate::custom_writers_and_readers::*;
+use crate::transaction::Transaction;
+use crate::TRANSFERS_FILE_PATH;
+
+pub const TRANSFERS_HEADERS: [&str; 5] = ["id", "from", "to", "reason", "moved_at"];
+
+impl Transfer {
+    pub fn to_csv(&self) -> [String; 5] {
+        [
+            self.id.to_string(),
+            optional_to_csv(self.from.as_ref()),
+            self.to.clone(),
+            self.reason.clone(),
+            self.moved_at.to_string(),
+        ]
+    }
+
+    pub fn from_csv(record: &StringRecord) -> Result<Transfer, Box<dyn Error>> {
+        Ok(Transfer {
+            id: record[0].parse()?,
+            from: optional_from_csv(&record[1])?,
+            to: record[2].to_string(),
+            reason: record[3].to_string(),
+            moved_at: record[4].parse()?,
+        })
+    }
+}
+
+impl<W: Durable> CustomWriter<W> {
+    pub fn write_transfer(&mut self, transfer: &Transfer) -> csv::Result<()> {
+        self.inner.write_record(transfer.to_csv())?;
+        self.flush()?;
+        Ok(())
+    }
+}
+
+pub trait TransferReader {
+    fn read_transfers_of(&mut self, id: u32) -> Result<Vec<Transfer>, Box<dyn Error>>;
+}
+
+impl<R: std::io::Read + std::io::Seek> TransferReader for CustomReader<R> {
+    fn read_transfers_of(&mut self, id: u32) -> Result<Vec<Transfer>, Box<dyn Error>> {
+        let mut transfers = Vec::new();
+        for result in self.inner.records() {
+            let transfer = Transfer::from_csv(&result?)?;
+            if transfer.id == id {
+                transfers.push(transfer);
+            }
+        }
+        self.seek_to_beginning()?;
+        Ok(transfers)
+    }
+}
+
+/// Stages transfers at the end of the log.
+pub fn log_transfers(
+    transaction: &mut Transaction,
+    transfers: &[Transfer],
+) -> Result<(), Box<dyn Error>> {
+    let rows = transfers
+        .iter()
+        .map(|transfer| transfer.to_csv().to_vec())
+        .collect::<Vec<_>>();
+    transaction.append_all(TRANSFERS_FILE_PATH, &rows)
+}
+
+pub fn writer_transfers() -> Result<CustomWriter<File>, Box<dyn Error>> {
+    create_writer_append_for_path(TRANSFERS_FILE_PATH)
+}
+
+pub fn reader_transfers() -> Result<CustomReader<File>, Box<dyn Error>> {
+    create_reader_for_path(TRANSFERS_FILE_PATH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::csv_files_creator::create_test_transfers;
+
+    #[test]
+    fn test_read_transfers_of() -> Result<(), Box<dyn Error>> {
+        create_test_transfers()?;
+
+        let born = Transfer {
+            id: 1,
+            from: None,
+            to: "barn".to_string(),
+            reason: "born".to_string(),
+            moved_at: 10,
+        };
+        let moved = Transfer {
+            id: 1,
+            from: Some("barn".to_string()),
+            to: "vivarium".to_string(),
+            reason: "quarantine".to_string(),
+            moved_at: 20,
+        };
+        let other = Transfer {
+            id: 2,
+            from: None,
+            to: "barn".to_string(),
+            reason: "spawned".to_string(),
+            moved_at: 15,
+        };
+        let mut writer = writer_for_test()?;
+        writer.write_transfer(&born)?;
+        writer.write_transfer(&other)?;
+        writer.write_transfer(&moved)?;
+
+        let mut reader = reader_for_test()?;
+
+        assert_eq!(reader.read_transfers_of(1)?, vec![born, moved]);
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+use std::error::Error;
+use std::fs;
+
+use crate::animal_structs::JournalEntry;
+use crate::journal_csv::{reader_journal, JournalQuery, JournalReader};
+use crate::temp_file_handler::write_file_durably;
+use crate::{JOURNAL_FILE_PATH, UNDO_FILE_PATH};
+
+/// How many operations can be undone at most, older ones being forgotten.
+pub const MAX_UNDO_STEPS: usize = 20;
+
+/// Numbering of the operations recorded in the journal, and which of them
+/// can be undone or redone, the latest last.
+#[derive(PartialEq, Debug, Default)]
+pub struct UndoHistory {
+    pub operation: u64,
+    pub undoable: Vec<u64>,
+    pub redoable: Vec<u64>,
+    /// Bytes of the journal when the operation began, its entries following.
+    pub journal_len: u64,
+}
+
+fn operations_to_text(operations: &[u64]) -> String {
+    operations
+        .iter()
+        .map(|operation| operation.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn operations_from_text(text: &str) -> Option<Vec<u64>> {
+    text.split_whitespace()
+        .map(|operation| operation.parse().ok())
+        .collect()
+}
+
+impl UndoHistory {
+    pub fn to_text(&self) -> String {
+        format!(
+            "{}\n{}\n{}\n{}",
+            self.operation,
+            operations_to_text(&self.undoable),
+            operations_to_text(&self.redoable),
+            self.journal_len
+        )
+    }
+
+    pub fn from_text(text: &str) -> Option<UndoHistory> {
+        let mut lines = text.lines();
+        Some(UndoHistory {
+            operation: lines.next()?.trim().parse().ok()?,
+            undoable: operations_from_text(lines.next().unwrap_or(""))?,
+            redoable: operations_from_text(lines.next().unwrap_or(""))?,
+            // Histories from before it was kept have the whole journal read once.
+            journal_len: lines
+                .next()
+                .map_or(Some(0), |len| len.trim().parse().ok())?,
+        })
+    }
+
+    /// Makes a finished operation the next to undo. Whatever was undone
+    /// before cannot be redone anymore.
+    pub fn done(&mut self, operation: u64) {
+        self.undoable.push(operation);
+        if self.undoable.len() > MAX_UNDO_STEPS {
+            self.undoable.remove(0);
+        }
+        self.redoable.clear();
+    }
+
+    /// Makes a redone operation the next to undo, keeping the others to redo.
+    pub fn redone(&mut self, operation: u64) {
+        self.redoable.retain(|redoable| *redoable != operation);
+        self.undoable.push(operation);
+        if self.undoable.len() > MAX_UNDO_STEPS {
+            self.undoable.remove(0);
+        }
+    }
+
+    pub fn undone(&mut self, operation: u64) {
+        self.undoable.retain(|undoable| *undoable != operation);
+        self.redoable.push(operation);
+    }
+}
+
+pub fn history() -> UndoHistory {
+    fs::read_to_string(UNDO_FILE_PATH)
+        .ok()
+        .and_then(|history| UndoHistory::from_text(&history))
+        .unwrap_or_default()
+}
+
+pub fn set_history(history: &UndoHistory) -> Result<(), Box<dyn Error>> {
+    write_file_durably(UNDO_FILE_PATH, &history.to_text())
+}
+
+fn journal_len() -> Result<u64, Box<dyn Error>> {
+    Ok(fs::metadata(JOURNAL_FILE_PATH)?.len())
+}
+
+/// Journal entries recorded since it was `len` bytes long. A journal now
+/// shorter than that was replaced, so it is read whole.
+fn journal_entries_since(
+    len: u64,
+    query: &JournalQuery,
+) -> Result<Vec<JournalEntry>, Box<dyn Error>> {
+    let since = if len <= journal_len()? { len } else { 0 };
+    reader_journal()?.read_journal_from(since, query)
+}
+
+/// Journal entries of the operation running.
+pub fn operation_entries() -> Result<Vec<JournalEntry>, Box<dyn Error>> {
+    let history = history();
+    let query = JournalQuery {
+        operation: Some(history.operation),
+        ..Default::default()
+    };
+    journal_entries_since(history.journal_len, &query)
+}
+
+/// Operation the journal entries written now belong to.
+pub fn current_operation() -> u64 {
+    history().operation
+}
+
+/// Starts numbering journal entries as a new operation. Numbers go on past
+/// any the journal got since the last operation began, so a lost history
+/// never makes operations share one, while only the journal's tail is read.
+pub fn begin_operation() -> Result<u64, Box<dyn Error>> {
+    let mut history = history();
+    let last_journaled = journal_entries_since(history.journal_len, &JournalQuery::default())?
+        .iter()
+        .map(|entry| entry.operation)
+        .max()
+        .unwrap_or(0);
+    history.operation = history.operation.max(last_journaled) + 1;
+    history.journal_len = journal_len()?;
+    set_history(&history)?;
+    Ok(history.operation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_history_text_roundtrip() {
+        let history = UndoHistory {
+            operation: 12,
+            undoable: vec![3, 7],
+            redoable: vec![11],
+            journal_len: 120,
+        };
+        assert_eq!(UndoHistory::from_text(&history.to_text()), Some(history));
+        assert_eq!(
+            UndoHistory::from_text("4\n\n"),
+            Some(UndoHistory {
+                operation: 4,
+                ..Default::default()
+            })
+        );
+        assert_eq!(UndoHistory::from_text("4\n1 x"), None);
+    }
+
+    #[test]
+    fn test_undo_history_is_bounded() {
+        let mut history = UndoHistory::default();
+        for operation in 1..=MAX_UNDO_STEPS as u64 + 5 {
+            history.done(operation);
+        }
+        assert_eq!(history.undoable.len(), MAX_UNDO_STEPS);
+        assert_eq!(history.undoable[0], 6);
+
+        history.undone(25);
+        history.undone(24);
+        assert_eq!(history.redoable, vec![25, 24]);
+        history.redone(24);
+        assert_eq!(history.redoable, vec![25]);
+        assert_eq!(history.undoable.last(), Some(&24));
+        history.done(26);
+        assert!(history.redoable.is_empty());
+    }
+}